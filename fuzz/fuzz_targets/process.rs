@@ -0,0 +1,258 @@
+//! Feeds arbitrary instruction data and accounts into the program, asserting that
+//! it never panics, and that successful instructions don't mint or burn lamports
+
+#![no_main]
+
+use std::sync::Once;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use mdp::{consts::ER_RECORD_SEED, entrypoint::process, state::config::Config, ID};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{
+        deserialize, ProgramResult, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER,
+        SUCCESS,
+    },
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+    sysvar::instructions,
+};
+
+/// Maximum number of accounts passed to a single instruction
+const MAX_ACCOUNTS: usize = 16;
+/// Slot and unix timestamp, which the clock sysvar reports
+const SLOT: u64 = 1_000_000;
+const NOW: i64 = 1_700_000_000;
+/// Tags of the system program instructions, which the program invokes
+const CREATE_ACCOUNT: u32 = 0;
+const TRANSFER: u32 = 2;
+
+/// Account address, biased towards the ones the program actually checks against
+#[derive(Arbitrary, Debug, Clone, Copy)]
+enum Key {
+    Program,
+    SystemProgram,
+    Config,
+    InstructionsSysvar,
+    /// Record PDA of the given ER node identity
+    Record([u8; 32]),
+    Raw([u8; 32]),
+}
+
+impl Key {
+    fn pubkey(self) -> Pubkey {
+        match self {
+            Self::Program => ID,
+            Self::SystemProgram => system_program::ID,
+            Self::Config => Config::pda().0,
+            Self::InstructionsSysvar => instructions::ID,
+            Self::Record(identity) => {
+                Pubkey::find_program_address(&[ER_RECORD_SEED, &identity], &ID).0
+            }
+            Self::Raw(key) => Pubkey::new_from_array(key),
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+struct Account {
+    key: Key,
+    owner: Key,
+    is_signer: bool,
+    is_writable: bool,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    accounts: Vec<Account>,
+    data: Vec<u8>,
+}
+
+/// Serializes accounts and instruction data in the same layout, which the runtime uses
+/// to pass them to the program, so that the accounts can be reallocated in place,
+/// accounts sharing the same address are passed as duplicates of the first one
+fn serialize(input: &Input) -> Vec<u64> {
+    let accounts = &input.accounts[..input.accounts.len().min(MAX_ACCOUNTS)];
+    let mut buf = Vec::new();
+    buf.extend((accounts.len() as u64).to_le_bytes());
+    let mut keys = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        let key = account.key.pubkey();
+        if let Some(index) = keys.iter().position(|k| *k == key) {
+            buf.push(index as u8);
+            buf.extend([0; 7]);
+            keys.push(key);
+            continue;
+        }
+        keys.push(key);
+        buf.extend([
+            NON_DUP_MARKER,
+            account.is_signer as u8,
+            account.is_writable as u8,
+            0,
+        ]);
+        buf.extend([0; 4]);
+        buf.extend(key.to_bytes());
+        buf.extend(account.owner.pubkey().to_bytes());
+        buf.extend(account.lamports.to_le_bytes());
+        buf.extend((account.data.len() as u64).to_le_bytes());
+        buf.extend(&account.data);
+        buf.resize(buf.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+        buf.resize(buf.len().next_multiple_of(BPF_ALIGN_OF_U128), 0);
+        buf.extend(u64::MAX.to_le_bytes());
+    }
+    buf.extend((input.data.len() as u64).to_le_bytes());
+    buf.extend(&input.data);
+    buf.extend(ID.to_bytes());
+
+    // u64 backing storage keeps the lamports and data lengths aligned
+    buf.chunks(8)
+        .map(|chunk| {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u64::from_ne_bytes(word)
+        })
+        .collect()
+}
+
+/// Total lamports held by the distinct accounts
+fn total_lamports(accounts: &[AccountInfo]) -> u128 {
+    let mut seen = Vec::with_capacity(accounts.len());
+    accounts
+        .iter()
+        .filter(|a| {
+            let unique = !seen.contains(&a.key);
+            seen.push(a.key);
+            unique
+        })
+        .map(|a| a.lamports() as u128)
+        .sum()
+}
+
+/// Syscalls of the runtime, reduced to the sysvars and the system program
+/// instructions, which the program uses
+struct Runtime;
+
+impl Runtime {
+    fn system_program(
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let find = |key: &Pubkey| {
+            accounts
+                .iter()
+                .find(|a| a.key == key)
+                .ok_or(ProgramError::NotEnoughAccountKeys)
+        };
+        for meta in ix.accounts.iter().filter(|m| m.is_signer) {
+            let signed = signers_seeds.iter().any(|seeds| {
+                Pubkey::create_program_address(seeds, &ID).is_ok_and(|pda| pda == meta.pubkey)
+            });
+            if !(find(&meta.pubkey)?.is_signer || signed) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+        }
+        let [from, to, ..] = &ix.accounts[..] else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        let (from, to) = (find(&from.pubkey)?, find(&to.pubkey)?);
+        if *from.owner != system_program::ID || !from.data_is_empty() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let read = |range: std::ops::Range<usize>| {
+            ix.data
+                .get(range)
+                .ok_or(ProgramError::InvalidInstructionData)
+        };
+        let tag = u32::from_le_bytes(read(0..4)?.try_into().unwrap());
+        let lamports = u64::from_le_bytes(read(4..12)?.try_into().unwrap());
+        if tag == CREATE_ACCOUNT && (to.lamports() != 0 || !to.data_is_empty()) {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if tag != CREATE_ACCOUNT && tag != TRANSFER {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let balance = from
+            .lamports()
+            .checked_sub(lamports)
+            .ok_or(ProgramError::InsufficientFunds)?;
+        **from.try_borrow_mut_lamports()? = balance;
+        let balance = to
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **to.try_borrow_mut_lamports()? = balance;
+
+        if tag == CREATE_ACCOUNT {
+            let space = u64::from_le_bytes(read(12..20)?.try_into().unwrap());
+            let owner = Pubkey::try_from(read(20..52)?).unwrap();
+            to.realloc(space as usize, true)?;
+            to.assign(&owner);
+        }
+        Ok(())
+    }
+}
+
+impl SyscallStubs for Runtime {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_log_data(&self, _fields: &[&[u8]]) {}
+
+    fn sol_invoke_signed(
+        &self,
+        ix: &Instruction,
+        accounts: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if ix.program_id != system_program::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::system_program(ix, accounts, signers_seeds)
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            slot: SLOT,
+            unix_timestamp: NOW,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+}
+
+static RUNTIME: Once = Once::new();
+
+fuzz_target!(|input: Input| {
+    RUNTIME.call_once(|| {
+        set_syscall_stubs(Box::new(Runtime));
+    });
+    let mut buf = serialize(&input);
+    // SAFETY: the buffer is laid out exactly as the runtime serializes program input
+    let (program_id, accounts, data) = unsafe { deserialize(buf.as_mut_ptr() as *mut u8) };
+
+    let before = total_lamports(&accounts);
+    if process(program_id, &accounts, data).is_ok() {
+        assert_eq!(
+            before,
+            total_lamports(&accounts),
+            "lamports aren't conserved"
+        );
+    }
+});
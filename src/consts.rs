@@ -1,2 +1,50 @@
 /// Seed string used in PDA derivation for domain registry record
 pub const ER_RECORD_SEED: &[u8] = b"er-record";
+
+/// Seed string used in PDA derivation for program configuration
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Seed string used in PDA derivation for human readable name record
+pub const ER_NAME_SEED: &[u8] = b"er-name";
+
+/// Seed string used in PDA derivation for reverse (identity to name) lookup record
+pub const ER_REVERSE_NAME_SEED: &[u8] = b"er-reverse-name";
+
+/// Seed string used in PDA derivation for rolling attestation counters of ER node
+pub const ER_ATTESTATION_SEED: &[u8] = b"er-attestation";
+
+/// Seed string used in PDA derivation for reputation score of ER node
+pub const ER_REPUTATION_SEED: &[u8] = b"er-reputation";
+
+/// Seed string used in PDA derivation for bond vault of ER node
+pub const ER_BOND_SEED: &[u8] = b"er-bond";
+
+/// Seed string used in PDA derivation for dispute against ER node
+pub const ER_DISPUTE_SEED: &[u8] = b"er-dispute";
+
+/// Seed string used in PDA derivation for raw TEE attestation quote of ER node
+pub const ER_TEE_QUOTE_SEED: &[u8] = b"er-tee-quote";
+
+/// Maximum length of raw TEE attestation quote in bytes, enough for DCAP
+/// quotes along with embedded certificate chain
+pub const MAX_TEE_QUOTE_LEN: usize = 8 * 1024;
+
+/// Seed string used in PDA derivation for pages of secondary indexes of ER records
+pub const ER_INDEX_SEED: &[u8] = b"er-index";
+
+/// Maximum length of human readable name in bytes
+pub const MAX_NAME_LEN: usize = 128;
+
+/// Maximum number of records which can be updated by single BatchSync instruction,
+/// chosen so that a batch of status only updates fits into a legacy transaction
+pub const MAX_BATCH_SYNC: usize = 10;
+
+/// Maximum number of maintenance windows, which can be announced in the record
+pub const MAX_MAINTENANCE_WINDOWS: usize = 8;
+
+/// Maximum number of keys, which can be allowed to attest observations in program config
+pub const MAX_ATTESTERS: usize = 32;
+
+/// Domain separator prepended to relayed sync messages before signing,
+/// prevents signatures over other payloads from being accepted as syncs
+pub const RELAYED_SYNC_DOMAIN: &[u8] = b"mdp-relayed-sync";
@@ -0,0 +1,276 @@
+use std::cmp::Reverse;
+
+use solana_program::pubkey::Pubkey;
+
+use crate::state::{
+    attestation::AttestationRecord, geo::GeoPoint, record::ErRecord, semver::SemVer,
+    status::ErStatus,
+};
+
+/// Client side filter for selecting ER nodes out of the fetched domain registry records
+#[derive(Debug, Clone, Copy)]
+pub struct Filter {
+    /// Unix timestamp (seconds) at which the selection is made
+    now: i64,
+    /// Minimum number of accounts, which can still be delegated to ER node
+    min_capacity: u32,
+    /// Minimum protocol version, which ER node should speak
+    min_protocol: Option<SemVer>,
+    /// Whether ER node should publish unexpired TEE attestation
+    tee_attested: bool,
+}
+
+impl Filter {
+    /// Creates filter, which only selects ER nodes that are
+    /// effectively active at the given unix timestamp
+    pub fn new(now: i64) -> Self {
+        Self {
+            now,
+            min_capacity: 0,
+            min_protocol: None,
+            tee_attested: false,
+        }
+    }
+
+    /// Only selects ER nodes, which speak at least the given protocol version
+    pub fn min_protocol(mut self, version: SemVer) -> Self {
+        self.min_protocol = Some(version);
+        self
+    }
+
+    /// Only selects ER nodes, which accept new delegations and can take at least
+    /// given number of accounts, nodes not advertising any limit are not filtered out
+    pub fn min_capacity(mut self, accounts: u32) -> Self {
+        self.min_capacity = accounts;
+        self
+    }
+
+    /// Only selects ER nodes running inside of TEE, which attestation hasn't expired,
+    /// the published quote should still be verified off chain before trusting the node
+    pub fn tee_attested(mut self) -> Self {
+        self.tee_attested = true;
+        self
+    }
+
+    /// Returns true if the given record satisfies all of the filter's criteria
+    pub fn matches(&self, record: &ErRecord) -> bool {
+        if record.effective_status(self.now) != ErStatus::Active {
+            return false;
+        }
+        if self
+            .min_protocol
+            .is_some_and(|min| record.protocol_version() < min)
+        {
+            return false;
+        }
+        if self.tee_attested && !record.tee_attested(self.now) {
+            return false;
+        }
+        if self.min_capacity != 0 {
+            let remaining = record.metrics().remaining_capacity();
+            if !record.accepting_delegations(self.now)
+                || remaining.is_some_and(|r| r < self.min_capacity)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Selects records, which match the filter, out of the given ones
+    pub fn apply<'a>(
+        &'a self,
+        records: impl IntoIterator<Item = &'a ErRecord> + 'a,
+    ) -> impl Iterator<Item = &'a ErRecord> + 'a {
+        records.into_iter().filter(|r| self.matches(r))
+    }
+}
+
+/// Orders ER nodes by their quality attested by clients within the rolling window ending
+/// at given slot, i.e. by success rate of observed requests, then by average latency,
+/// nodes without any observations go last, `fetch` should return attestation record
+/// of the given ER node identity, if it exists
+pub fn rank<'a>(
+    records: impl IntoIterator<Item = &'a ErRecord>,
+    slot: u64,
+    mut fetch: impl FnMut(&Pubkey) -> Option<AttestationRecord>,
+) -> Vec<&'a ErRecord> {
+    let mut ranked: Vec<_> = records
+        .into_iter()
+        .map(|r| {
+            let stats = fetch(r.identity())
+                .map(|a| a.stats(slot))
+                .unwrap_or_default();
+            let latency = stats.avg_latency_ms().unwrap_or(u64::MAX);
+            ((Reverse(stats.success_rate_bps()), latency), r)
+        })
+        .collect();
+    ranked.sort_by_key(|(key, _)| *key);
+    ranked.into_iter().map(|(_, r)| r).collect()
+}
+
+/// Orders ER nodes by great-circle distance from the given client location, nearest first,
+/// nodes without announced location go last, keeping their relative order
+pub fn by_distance<'a>(
+    records: impl IntoIterator<Item = &'a ErRecord>,
+    from: &GeoPoint,
+) -> Vec<&'a ErRecord> {
+    let mut sorted: Vec<_> = records
+        .into_iter()
+        .map(|r| (r.location().map(|l| l.distance_km(from)), r))
+        .collect();
+    sorted.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(b),
+        _ => b.is_some().cmp(&a.is_some()),
+    });
+    sorted.into_iter().map(|(_, r)| r).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_program::hash::Hash;
+
+    use super::*;
+    use crate::state::{
+        attestation::Observation,
+        features::FeaturesSet,
+        fees::FeeSchedule,
+        maintenance::{MaintenanceReason, MaintenanceWindow},
+        metrics::LoadMetrics,
+        record::CountryCode,
+        version::v1::RecordV1,
+    };
+
+    fn record(status: ErStatus, active: u32, max: u32) -> ErRecord {
+        let identity = Pubkey::new_unique();
+        ErRecord::V1(RecordV1 {
+            identity,
+            authority: identity,
+            cluster: Hash::default(),
+            chain_id: 0,
+            status,
+            block_time_ms: 50,
+            features: FeaturesSet::default(),
+            load_average: 0,
+            metrics: LoadMetrics {
+                active_delegations: active,
+                max_delegations: max,
+                ..Default::default()
+            },
+            country_code: CountryCode::from("083"),
+            software_version: SemVer::new(0, 1, 0),
+            protocol_version: SemVer::new(1, 0, 0),
+            sequence: 0,
+            registered_slot: 0,
+            heartbeats: Default::default(),
+            fees: FeeSchedule::default(),
+            pending_fees: None,
+            exit_after: None,
+            tee: None,
+            tls_fingerprint: None,
+            region: None,
+            location: None,
+            addr: "https://241.132.2.41:9324/".to_string(),
+            maintenance: vec![],
+        })
+    }
+
+    #[test]
+    fn test_filter_status() {
+        let mut maintained = record(ErStatus::Active, 0, 0);
+        maintained.set_maintenance(vec![MaintenanceWindow {
+            start: 100,
+            end: 200,
+            reason: MaintenanceReason::Upgrade,
+        }]);
+        let records = [
+            record(ErStatus::Active, 0, 0),
+            record(ErStatus::Draining, 0, 0),
+            record(ErStatus::Offline, 0, 0),
+            record(ErStatus::Deregistering, 0, 0),
+            maintained,
+        ];
+        assert_eq!(Filter::new(150).apply(&records).count(), 1);
+        assert_eq!(Filter::new(250).apply(&records).count(), 2);
+    }
+
+    #[test]
+    fn test_filter_capacity() {
+        let records = [
+            record(ErStatus::Active, 0, 0),
+            record(ErStatus::Active, 100, 100),
+            record(ErStatus::Active, 90, 100),
+            record(ErStatus::Draining, 0, 100),
+        ];
+        assert_eq!(Filter::new(0).apply(&records).count(), 3);
+        assert_eq!(Filter::new(0).min_capacity(1).apply(&records).count(), 2);
+        assert_eq!(Filter::new(0).min_capacity(20).apply(&records).count(), 1);
+    }
+
+    #[test]
+    fn test_filter_protocol() {
+        let mut records = [
+            record(ErStatus::Active, 0, 0),
+            record(ErStatus::Active, 0, 0),
+        ];
+        records[1].set_protocol_version(SemVer::new(1, 2, 0));
+        let filter = Filter::new(0).min_protocol(SemVer::new(1, 1, 0));
+        assert_eq!(filter.apply(&records).count(), 1);
+    }
+
+    #[test]
+    fn test_rank_by_attestations() {
+        let records = [
+            record(ErStatus::Active, 0, 0),
+            record(ErStatus::Active, 0, 0),
+            record(ErStatus::Active, 0, 0),
+            record(ErStatus::Active, 0, 0),
+        ];
+        // (successes, failures, latency) observed for each node, last one is unobserved
+        let observed = [(8, 2, 20), (10, 0, 80), (10, 0, 30)];
+        let attestations: Vec<_> = records
+            .iter()
+            .zip(observed)
+            .map(|(r, (successes, failures, latency_ms))| {
+                let mut attestation = AttestationRecord::new(*r.identity());
+                for i in 0..successes + failures {
+                    attestation.record(&Observation {
+                        identity: *r.identity(),
+                        slot: 10,
+                        latency_ms,
+                        success: i < successes,
+                    });
+                }
+                attestation
+            })
+            .collect();
+        let fetch = |id: &Pubkey| attestations.iter().find(|a| a.identity == *id).cloned();
+        let ranked = rank(&records, 20, fetch);
+        let order: Vec<_> = ranked.iter().map(|r| *r.identity()).collect();
+        let expected: Vec<_> = [2, 1, 0, 3].map(|i| *records[i].identity()).to_vec();
+        assert_eq!(order, expected);
+    }
+
+    #[test]
+    fn test_sort_by_distance() {
+        let mut records = [
+            record(ErStatus::Active, 0, 0),
+            record(ErStatus::Active, 0, 0),
+            record(ErStatus::Active, 0, 0),
+        ];
+        // US west and east coast nodes, the last one doesn't announce its location
+        records[0].set_location(Some(GeoPoint::from_degrees(37.3, -121.9)));
+        records[1].set_location(Some(GeoPoint::from_degrees(39.0, -77.5)));
+        let chicago = GeoPoint::from_degrees(41.9, -87.6);
+        let seattle = GeoPoint::from_degrees(47.6, -122.3);
+        let order = |from| -> Vec<_> {
+            by_distance(&records, &from)
+                .iter()
+                .map(|r| *r.identity())
+                .collect()
+        };
+        let expected = |idx: [usize; 3]| idx.map(|i| *records[i].identity()).to_vec();
+        assert_eq!(order(chicago), expected([1, 0, 2]));
+        assert_eq!(order(seattle), expected([0, 1, 2]));
+    }
+}
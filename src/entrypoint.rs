@@ -1,12 +1,51 @@
-use crate::{instructions::Instruction, processors::*};
+use crate::{error::MdpError, instructions::Instruction, processors::*};
 use borsh::BorshDeserialize;
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
     pubkey::Pubkey,
 };
 
+#[cfg(not(feature = "zero-copy"))]
 solana_program::entrypoint!(process);
 
+/// Program entrypoint, which deserializes accounts without heap allocations, and applies
+/// syncs of the fixed size fields directly to the serialized record, other instructions
+/// are handed over to [process]
+///
+/// # Safety
+/// `input` should point to the serialized program input, as provided by the runtime
+#[cfg(feature = "zero-copy")]
+#[no_mangle]
+pub unsafe extern "C" fn entrypoint(input: *mut u8) -> u64 {
+    use solana_program::entrypoint::{deserialize_into, SUCCESS};
+    use std::mem::MaybeUninit;
+
+    /// Maximum number of accounts, the entrypoint accepts, same as in `entrypoint_no_alloc`
+    const MAX_ACCOUNTS: usize = 64;
+    #[allow(clippy::declare_interior_mutable_const)]
+    const UNINIT: MaybeUninit<AccountInfo> = MaybeUninit::uninit();
+
+    let mut accounts = [UNINIT; MAX_ACCOUNTS];
+    let (program_id, count, data) = deserialize_into(input, &mut accounts);
+    // SAFETY: the first `count` accounts are initialized by `deserialize_into`
+    let accounts =
+        &*(&accounts[..count] as *const [MaybeUninit<AccountInfo>] as *const [AccountInfo]);
+
+    let fast = (*program_id == crate::ID)
+        .then(|| fast_sync::try_process_fast_sync(accounts, data))
+        .flatten();
+    let result = fast.unwrap_or_else(|| process(program_id, accounts, data));
+    match result {
+        Ok(()) => SUCCESS,
+        Err(error) => error.into(),
+    }
+}
+
+#[cfg(feature = "zero-copy")]
+solana_program::custom_heap_default!();
+#[cfg(feature = "zero-copy")]
+solana_program::custom_panic_default!();
+
 /// Main program entrypoint for processing supported instructions
 pub fn process<'a>(
     program_id: &Pubkey,
@@ -20,7 +59,38 @@ pub fn process<'a>(
     let accounts = accounts.iter();
     match ix {
         Instruction::Register(record) => register::process_registration(accounts, record),
+        Instruction::Unregister(_) => {
+            msg!("Unregister is replaced by BeginUnregister followed by CompleteUnregister");
+            Err(MdpError::RemovedInstruction.into())
+        }
         Instruction::Sync(ix) => sync::process_sync_record(accounts, ix),
-        Instruction::Unregister(node_id) => unregister::process_unregistration(accounts, node_id),
+        Instruction::RelayedSync(ix) => relay::process_relayed_sync(accounts, ix),
+        Instruction::BatchSync(batch) => batch::process_batch_sync(accounts, batch),
+        Instruction::ClaimName(name, identity) => {
+            name::process_claim_name(accounts, name, identity)
+        }
+        Instruction::ReleaseName(name) => name::process_release_name(accounts, name),
+        Instruction::TransferName(name, new_owner) => {
+            name::process_transfer_name(accounts, name, new_owner)
+        }
+        Instruction::InitConfig(config) => config::process_init_config(accounts, config),
+        Instruction::UpdateConfig(config) => config::process_update_config(accounts, config),
+        Instruction::Attest(observation) => attest::process_attest(accounts, observation),
+        Instruction::UpdateReputation(identity) => {
+            reputation::process_update_reputation(accounts, identity)
+        }
+        Instruction::Bond(lamports) => dispute::process_bond(accounts, lamports),
+        Instruction::OpenDispute(claim) => dispute::process_open_dispute(accounts, claim),
+        Instruction::ResolveDispute(ruling) => dispute::process_resolve_dispute(accounts, ruling),
+        Instruction::CompleteUnregister(node_id) => {
+            unregister::process_complete_unregistration(accounts, node_id)
+        }
+        Instruction::WriteTeeQuote(chunk) => tee::process_write_tee_quote(accounts, chunk),
+        Instruction::PublishTeeQuote(publication) => {
+            tee::process_publish_tee_quote(accounts, publication)
+        }
+        Instruction::BeginUnregister(node_id) => {
+            unregister::process_begin_unregistration(accounts, node_id)
+        }
     }
 }
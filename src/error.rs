@@ -0,0 +1,78 @@
+use solana_program::program_error::ProgramError;
+
+/// Custom errors returned by domain registry program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum MdpError {
+    /// Sync sequence number is not strictly greater than the one stored in the record,
+    /// i.e. the update is either replayed or older than the already applied one, legacy
+    /// syncs without sequence number are always rejected with it
+    StaleSequence = 0,
+    /// Batch sync contains more updates than allowed by [crate::consts::MAX_BATCH_SYNC]
+    BatchTooLarge = 1,
+    /// Maintenance window ends before it starts, or there are more of them
+    /// than allowed by [crate::consts::MAX_MAINTENANCE_WINDOWS]
+    InvalidMaintenance = 2,
+    /// Name is not in its normalized form, see [crate::state::name::normalize]
+    InvalidName = 3,
+    /// ER record is advertised for different base chain cluster than the registry is deployed to
+    ClusterMismatch = 4,
+    /// Fee schedule has several surcharges for the same feature, or priority
+    /// multiplier which discounts the transactions, or fee increase is
+    /// announced on registration, bypassing the notice period
+    InvalidFees = 5,
+    /// Observation is made by ER node itself, or its slot is in the future
+    /// or outside of the rolling attestation window
+    InvalidObservation = 6,
+    /// Dispute has already been resolved
+    DisputeNotOpen = 7,
+    /// ER node's bond, which isn't at stake in other disputes, cannot match the counter bond
+    InsufficientBond = 8,
+    /// ER node exit hasn't been started, or its cooldown hasn't passed yet
+    ExitNotReady = 9,
+    /// ER node cannot exit while there are open disputes against it
+    DisputesPending = 10,
+    /// Deregistering status can only be entered by starting the exit, and cannot be left
+    Deregistering = 11,
+    /// TEE quote chunk is out of bounds, or the uploaded quote cannot be
+    /// parsed, or its attestation is already expired, or the attestation is set
+    /// by other means than publishing the quote
+    InvalidTeeQuote = 12,
+    /// Announced geographic coordinates are out of their valid ranges
+    InvalidLocation = 13,
+    /// Index page affected by the change of record isn't passed to the instruction
+    MissingIndexPage = 14,
+    /// Transaction signer isn't the ER node identity, which the instruction refers to
+    IdentityMismatch = 15,
+    /// Account isn't the PDA derived for the ER node, name, config or any other
+    /// program state the instruction refers to
+    InvalidPda = 16,
+    /// Transaction signer isn't allowed to perform the instruction, i.e. it's not the
+    /// authority of the record, config admin, arbiter, attester or upgrade authority, or name owner
+    Unauthorized = 17,
+    /// Instruction is no longer supported, i.e. immediate Unregister, which
+    /// is replaced by BeginUnregister followed by CompleteUnregister
+    RemovedInstruction = 18,
+    /// Lamports released by the instruction cannot be sent to the given account,
+    /// e.g. to the record or bond PDA, which is being closed
+    InvalidRecipient = 19,
+    /// Dispute is opened by the ER node against itself, or without counter bond
+    InvalidDisputeClaim = 20,
+    /// Treasury account doesn't match the one set in program config
+    TreasuryMismatch = 21,
+    /// Challenger account doesn't match the one, which opened the dispute
+    ChallengerMismatch = 22,
+    /// Program data account isn't the one of this program deployed via upgradeable loader
+    InvalidProgramData = 23,
+    /// Program config is malformed, e.g. initial admin isn't the upgrade authority,
+    /// unbonding cooldown is negative, or too many attesters are allowed
+    InvalidConfig = 24,
+    /// Name cannot be released while it still has children
+    NameHasChildren = 25,
+}
+
+impl From<MdpError> for ProgramError {
+    fn from(value: MdpError) -> Self {
+        ProgramError::Custom(value as u32)
+    }
+}
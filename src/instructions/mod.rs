@@ -1,16 +1,61 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use relay::RelayedSync;
 use solana_program::pubkey::Pubkey;
 use sync::SyncInstruction;
 
-use crate::state::record::ErRecord;
+use crate::state::{
+    attestation::Observation,
+    config::Config,
+    dispute::{DisputeClaim, Ruling},
+    record::ErRecord,
+    tee::{QuoteChunk, QuotePublication},
+};
 
+pub mod relay;
 pub mod sync;
 pub mod version;
 
 /// Supported program instructions
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+#[allow(clippy::large_enum_variant)] // decoded once per transaction
 pub enum Instruction {
     Register(ErRecord),
+    /// Immediate removal of ER node, no longer supported in favour of two step exit
+    /// ([Instruction::BeginUnregister] and [Instruction::CompleteUnregister]), the
+    /// variant is kept, so that discriminants of the deployed instructions don't change
     Unregister(Pubkey),
     Sync(SyncInstruction),
+    RelayedSync(RelayedSync),
+    /// Updates multiple records sharing the same authority, record PDAs are passed
+    /// after the authority and system program in the same order as the updates
+    BatchSync(Vec<SyncInstruction>),
+    /// Claims normalized human readable name, optionally resolving to ER node identity
+    ClaimName(String, Option<Pubkey>),
+    /// Releases human readable name, removing both forward and reverse mappings
+    ReleaseName(String),
+    /// Transfers ownership of human readable name to the given pubkey
+    TransferName(String, Pubkey),
+    /// Initializes program configuration, signed by program upgrade authority
+    InitConfig(Config),
+    /// Replaces program configuration, signed by its admin, migrating it to the current layout
+    UpdateConfig(Config),
+    /// Records client's observation of ER node in its rolling attestation counters,
+    /// signed by one of the attesters allowed in program config
+    Attest(Observation),
+    /// Recomputes reputation score of ER node with the given identity, can be sent by anyone
+    UpdateReputation(Pubkey),
+    /// Posts given amount of lamports to the bond of ER node, signed by its identity
+    Bond(u64),
+    /// Opens dispute against bonded ER node, posting counter bond
+    OpenDispute(DisputeClaim),
+    /// Resolves open dispute, signed by admin or arbiter
+    ResolveDispute(Ruling),
+    /// Completes the exit of ER node after the cooldown, releasing its record and bond
+    CompleteUnregister(Pubkey),
+    /// Writes chunk of raw TEE attestation quote into quote record of ER node
+    WriteTeeQuote(QuoteChunk),
+    /// Publishes uploaded TEE quote into ER node's record, advertising TEE attestation
+    PublishTeeQuote(QuotePublication),
+    /// Starts the exit of ER node, hiding it from discovery until it's completed
+    BeginUnregister(Pubkey),
 }
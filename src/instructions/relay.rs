@@ -0,0 +1,121 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{ed25519_program, instruction::Instruction, pubkey::Pubkey};
+
+use crate::{consts::RELAYED_SYNC_DOMAIN, ID};
+
+use super::sync::SyncInstruction;
+
+/// Size of the ed25519 signature offsets header (count, padding and single offsets entry)
+const ED25519_HEADER_SIZE: usize = 16;
+const ED25519_PUBKEY_SIZE: usize = 32;
+const ED25519_SIGNATURE_SIZE: usize = 64;
+
+/// Sync instruction signed off-chain by ER node identity,
+/// which can be submitted (and paid for) by any relayer,
+/// replays are prevented by the sequence number of the sync
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct RelayedSync {
+    /// Record update to be applied
+    pub sync: SyncInstruction,
+}
+
+impl RelayedSync {
+    /// Returns the exact message, which ER node identity should sign off-chain
+    pub fn message(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(RELAYED_SYNC_DOMAIN.len() + 64);
+        message.extend_from_slice(RELAYED_SYNC_DOMAIN);
+        message.extend_from_slice(ID.as_ref());
+        // serialization into Vec is infallible
+        let _ = self.serialize(&mut message);
+        message
+    }
+
+    /// Builds ed25519 precompile instruction, verifying the signature of ER node
+    /// identity over the relayed message, it must immediately precede the sync
+    pub fn verify_instruction(&self, signature: &[u8; ED25519_SIGNATURE_SIZE]) -> Instruction {
+        let message = self.message();
+        let pubkey_offset = ED25519_HEADER_SIZE;
+        let signature_offset = pubkey_offset + ED25519_PUBKEY_SIZE;
+        let message_offset = signature_offset + ED25519_SIGNATURE_SIZE;
+
+        let mut data = Vec::with_capacity(message_offset + message.len());
+        // single signature, followed by padding byte
+        data.extend_from_slice(&[1, 0]);
+        for offset in [
+            signature_offset as u16,
+            u16::MAX,
+            pubkey_offset as u16,
+            u16::MAX,
+            message_offset as u16,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        data.extend_from_slice(self.sync.identity().as_ref());
+        data.extend_from_slice(signature);
+        data.extend_from_slice(&message);
+
+        Instruction {
+            program_id: ed25519_program::ID,
+            accounts: vec![],
+            data,
+        }
+    }
+
+    /// Extracts signer pubkey and signed message from ed25519 precompile instruction data,
+    /// only single signature instructions, carrying all of the data inline, are accepted
+    pub fn parse_verify_instruction(data: &[u8]) -> Option<(Pubkey, &[u8])> {
+        let header = data.get(..ED25519_HEADER_SIZE)?;
+        if header[0] != 1 {
+            return None;
+        }
+        let offset = |i: usize| u16::from_le_bytes([header[2 + i * 2], header[3 + i * 2]]);
+        let [_, signature_ix, pubkey_offset, pubkey_ix, message_offset, message_size, message_ix] =
+            [0, 1, 2, 3, 4, 5, 6].map(offset);
+        if [signature_ix, pubkey_ix, message_ix] != [u16::MAX; 3] {
+            return None;
+        }
+        let pubkey_offset = pubkey_offset as usize;
+        let pubkey = data.get(pubkey_offset..pubkey_offset + ED25519_PUBKEY_SIZE)?;
+        let message_offset = message_offset as usize;
+        let message = data.get(message_offset..message_offset + message_size as usize)?;
+        Some((Pubkey::try_from(pubkey).ok()?, message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::version::v1::SyncRecordV1;
+
+    #[test]
+    fn test_verify_instruction_roundtrip() {
+        let identity = Pubkey::new_unique();
+        let relayed = RelayedSync {
+            sync: SyncInstruction::V1(SyncRecordV1 {
+                identity,
+                sequence: 42,
+                status: None,
+                block_time_ms: None,
+                features: None,
+                load_average: Some(1_500_000),
+                metrics: None,
+                country_code: None,
+                region: None,
+                location: None,
+                software_version: None,
+                protocol_version: None,
+                fees: None,
+                addr: None,
+                tls_fingerprint: None,
+                maintenance: None,
+            }),
+        };
+        let ix = relayed.verify_instruction(&[7; 64]);
+        let (signer, message) = RelayedSync::parse_verify_instruction(&ix.data).unwrap();
+        assert_eq!(signer, identity);
+        assert_eq!(message, relayed.message());
+        assert!(RelayedSync::parse_verify_instruction(&ix.data[..20]).is_none());
+    }
+}
@@ -1,18 +1,61 @@
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::{
+    io::{self, Read, Write},
+    BorshDeserialize, BorshSerialize,
+};
 use solana_program::pubkey::Pubkey;
 
 use crate::{
     consts::ER_RECORD_SEED,
-    state::{features::FeaturesSet, record::CountryCode, status::ErStatus},
+    state::{
+        features::FeaturesSet,
+        fees::FeeSchedule,
+        geo::{GeoPoint, Region},
+        maintenance::MaintenanceWindow,
+        metrics::LoadMetrics,
+        record::CountryCode,
+        semver::SemVer,
+        status::ErStatus,
+        tls::SpkiFingerprint,
+    },
     ID,
 };
 
-use super::version::v0::SyncRecordV0;
+use super::version::{v0::SyncRecordV0, v1::SyncRecordV1};
+
+/// Borsh tag of the legacy sync layout, see [SyncRecordV0]
+const V0_TAG: u8 = 0;
+/// Borsh tag of the current sync layout, see [SyncRecordV1]
+pub const V1_TAG: u8 = 1;
 
-/// Versioned sync program instruction
-#[derive(BorshSerialize, BorshDeserialize)]
+/// Versioned sync program instruction, syncs sent in the legacy [SyncRecordV0] layout
+/// are decoded into the current one, but get rejected as stale, lacking sequence number
+#[derive(Debug)]
 pub enum SyncInstruction {
-    V0(SyncRecordV0),
+    V1(SyncRecordV1),
+}
+
+impl BorshSerialize for SyncInstruction {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Self::V1(v) => {
+                V1_TAG.serialize(writer)?;
+                v.serialize(writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for SyncInstruction {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match u8::deserialize_reader(reader)? {
+            V0_TAG => SyncRecordV0::deserialize_reader(reader).map(|v| Self::V1(v.into())),
+            V1_TAG => SyncRecordV1::deserialize_reader(reader).map(Self::V1),
+            tag => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown sync version {tag}"),
+            )),
+        }
+    }
 }
 
 impl SyncInstruction {
@@ -25,55 +68,112 @@ impl SyncInstruction {
     /// Returns identity pubkey of the ER node
     pub fn identity(&self) -> &Pubkey {
         match self {
-            Self::V0(r) => &r.identity,
+            Self::V1(r) => &r.identity,
+        }
+    }
+
+    /// Returns sequence number of the sync
+    pub fn sequence(&self) -> u64 {
+        match self {
+            Self::V1(v) => v.sequence,
         }
     }
 
     /// Returns address of the ER node, if set
     pub fn addr(&mut self) -> &mut Option<String> {
         match self {
-            Self::V0(v) => &mut v.addr,
+            Self::V1(v) => &mut v.addr,
         }
     }
 
-    /// Returns base transaction fee charged by ER node, if set
-    pub fn base_fee(&mut self) -> &mut Option<u16> {
+    /// Returns TLS certificate fingerprint update of the ER node, if set
+    pub fn tls_fingerprint(&mut self) -> &mut Option<Option<SpkiFingerprint>> {
         match self {
-            Self::V0(v) => &mut v.base_fee,
+            Self::V1(v) => &mut v.tls_fingerprint,
+        }
+    }
+
+    /// Returns fee schedule of the ER node, if set
+    pub fn fees(&mut self) -> &mut Option<FeeSchedule> {
+        match self {
+            Self::V1(v) => &mut v.fees,
         }
     }
 
     /// Returns the block time in ms of the given ER node, if set
     pub fn block_time_ms(&mut self) -> &mut Option<u16> {
         match self {
-            Self::V0(v) => &mut v.block_time_ms,
+            Self::V1(v) => &mut v.block_time_ms,
         }
     }
 
     /// Returns the features set supported by ER node, if set
     pub fn features(&mut self) -> &mut Option<FeaturesSet> {
         match self {
-            Self::V0(v) => &mut v.features,
+            Self::V1(v) => &mut v.features,
         }
     }
 
     /// Returns the status of ER node, if set
     pub fn status(&mut self) -> &mut Option<ErStatus> {
         match self {
-            Self::V0(v) => &mut v.status,
+            Self::V1(v) => &mut v.status,
         }
     }
 
     /// Returns last observed average load on the given ER node, if set
     pub fn load_average(&mut self) -> &mut Option<u32> {
         match self {
-            Self::V0(v) => &mut v.load_average,
+            Self::V1(v) => &mut v.load_average,
         }
     }
 
+    /// Returns structured load metrics of the given ER node, if set
+    pub fn metrics(&mut self) -> &mut Option<LoadMetrics> {
+        match self {
+            Self::V1(v) => &mut v.metrics,
+        }
+    }
+
+    /// Returns the country code of ER node, if set
     pub fn country_code(&mut self) -> &mut Option<CountryCode> {
         match self {
-            Self::V0(v) => &mut v.country_code,
+            Self::V1(v) => &mut v.country_code,
+        }
+    }
+
+    /// Returns geographic region update of ER node, if set
+    pub fn region(&mut self) -> &mut Option<Option<Region>> {
+        match self {
+            Self::V1(v) => &mut v.region,
+        }
+    }
+
+    /// Returns approximate location update of ER node, if set
+    pub fn location(&mut self) -> &mut Option<Option<GeoPoint>> {
+        match self {
+            Self::V1(v) => &mut v.location,
+        }
+    }
+
+    /// Returns the validator software version of ER node, if set
+    pub fn software_version(&mut self) -> &mut Option<SemVer> {
+        match self {
+            Self::V1(v) => &mut v.software_version,
+        }
+    }
+
+    /// Returns the protocol version of ER node, if set
+    pub fn protocol_version(&mut self) -> &mut Option<SemVer> {
+        match self {
+            Self::V1(v) => &mut v.protocol_version,
+        }
+    }
+
+    /// Returns the list of upcoming maintenance windows of ER node, if set
+    pub fn maintenance(&mut self) -> &mut Option<Vec<MaintenanceWindow>> {
+        match self {
+            Self::V1(v) => &mut v.maintenance,
         }
     }
 }
@@ -4,7 +4,7 @@ use solana_program::pubkey::Pubkey;
 use crate::state::{features::FeaturesSet, record::CountryCode, status::ErStatus};
 
 /// Sync instruction data, version 0
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
 pub struct SyncRecordV0 {
     pub identity: Pubkey,
     pub status: Option<ErStatus>,
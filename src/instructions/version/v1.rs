@@ -0,0 +1,69 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::state::{
+    features::FeaturesSet,
+    fees::FeeSchedule,
+    geo::{GeoPoint, Region},
+    maintenance::MaintenanceWindow,
+    metrics::LoadMetrics,
+    record::CountryCode,
+    semver::SemVer,
+    status::ErStatus,
+    tls::SpkiFingerprint,
+};
+
+use super::v0::SyncRecordV0;
+
+/// Sync instruction data, version 1
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SyncRecordV1 {
+    pub identity: Pubkey,
+    /// Strictly increasing sequence number, older or replayed syncs are rejected
+    pub sequence: u64,
+    pub status: Option<ErStatus>,
+    pub block_time_ms: Option<u16>,
+    pub features: Option<FeaturesSet>,
+    pub load_average: Option<u32>,
+    pub metrics: Option<LoadMetrics>,
+    pub country_code: Option<CountryCode>,
+    /// New geographic region, `Some(None)` removes it
+    pub region: Option<Option<Region>>,
+    /// New approximate location, `Some(None)` removes it
+    pub location: Option<Option<GeoPoint>>,
+    pub software_version: Option<SemVer>,
+    pub protocol_version: Option<SemVer>,
+    pub fees: Option<FeeSchedule>,
+    pub addr: Option<String>,
+    /// New TLS certificate fingerprint, `Some(None)` removes the pin
+    pub tls_fingerprint: Option<Option<SpkiFingerprint>>,
+    pub maintenance: Option<Vec<MaintenanceWindow>>,
+}
+
+impl From<SyncRecordV0> for SyncRecordV1 {
+    /// Legacy syncs carry no sequence number, so they are mapped onto sequence 0,
+    /// which is never greater than the stored one, i.e. they are rejected as stale
+    fn from(v: SyncRecordV0) -> Self {
+        Self {
+            identity: v.identity,
+            sequence: 0,
+            status: v.status,
+            block_time_ms: v.block_time_ms,
+            features: v.features,
+            load_average: v.load_average,
+            metrics: None,
+            country_code: v.country_code,
+            region: None,
+            location: None,
+            software_version: None,
+            protocol_version: None,
+            fees: v.base_fee.map(|base_fee| FeeSchedule {
+                lamports_per_signature: base_fee.into(),
+                ..Default::default()
+            }),
+            addr: v.addr,
+            tls_fingerprint: None,
+            maintenance: None,
+        }
+    }
+}
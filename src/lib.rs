@@ -3,7 +3,10 @@
 use solana_program::declare_id;
 
 pub mod consts;
+pub mod discovery;
+pub mod error;
 pub mod instructions;
+pub mod pinning;
 pub mod state;
 
 #[cfg(feature = "entrypoint")]
@@ -0,0 +1,80 @@
+use std::fmt;
+
+use solana_program::pubkey::Pubkey;
+
+use crate::state::{record::ErRecord, tls::SpkiFingerprint};
+
+/// Client side TLS verification policy for connections to ER node, which pins the
+/// certificate served at the node's address to the fingerprint published on chain
+/// by its identity, to be plugged into the certificate verifier of the TLS stack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinningPolicy {
+    identity: Pubkey,
+    addr: String,
+    fingerprint: SpkiFingerprint,
+}
+
+/// Reason of rejecting the certificate served by ER node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinningError {
+    /// Public key of the certificate doesn't match the published fingerprint
+    FingerprintMismatch {
+        expected: SpkiFingerprint,
+        actual: SpkiFingerprint,
+    },
+}
+
+impl PinningPolicy {
+    /// Builds policy out of ER node's record, returns `None` if the node
+    /// hasn't published fingerprint, in which case regular WebPKI
+    /// validation (or refusing to connect) is up to the client
+    pub fn from_record(record: &ErRecord) -> Option<Self> {
+        Some(Self {
+            identity: *record.identity(),
+            addr: record.addr().to_string(),
+            fingerprint: *record.tls_fingerprint()?,
+        })
+    }
+
+    /// Identity of ER node, which published the pin
+    pub fn identity(&self) -> &Pubkey {
+        &self.identity
+    }
+
+    /// Address of ER node, the pin applies to
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    /// Pinned fingerprint
+    pub fn fingerprint(&self) -> &SpkiFingerprint {
+        &self.fingerprint
+    }
+
+    /// Verifies DER encoded SubjectPublicKeyInfo of the end entity certificate presented
+    /// by ER node, the pin replaces hostname and chain validation, as the certificate of
+    /// node listening on a raw IP is usually self signed
+    pub fn verify(&self, spki_der: &[u8]) -> Result<(), PinningError> {
+        let actual = SpkiFingerprint::of(spki_der);
+        if actual != self.fingerprint {
+            return Err(PinningError::FingerprintMismatch {
+                expected: self.fingerprint,
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PinningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FingerprintMismatch { expected, actual } => write!(
+                f,
+                "certificate public key fingerprint {actual} doesn't match pinned {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PinningError {}
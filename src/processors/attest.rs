@@ -0,0 +1,81 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    msg,
+    program_error::ProgramError,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    consts::ER_ATTESTATION_SEED,
+    error::MdpError,
+    state::attestation::{
+        AttestationRecord, Observation, ATTESTATION_BUCKETS, ATTESTATION_BUCKET_SLOTS,
+    },
+};
+
+use super::{
+    config::load_config,
+    utils::{load_pda_if_exists, load_record, store_pda},
+};
+
+/// Records observation of ER node made by one of the attesters allowed in program config
+/// in node's rolling attestation counters, creating attestation record (paid for by the
+/// attester) on first use
+pub fn process_attest<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    observation: Observation,
+) -> Result<(), ProgramError> {
+    let attester = next_account_info(&mut accounts)?;
+    let attestation_account = next_account_info(&mut accounts)?;
+    let record_account = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+    let config_account = next_account_info(&mut accounts)?;
+
+    if !attester.is_signer {
+        msg!("attester should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *attester.key == observation.identity {
+        msg!("ER node cannot attest to itself");
+        return Err(MdpError::InvalidObservation.into());
+    }
+
+    let config = load_config(config_account)?;
+    if !config.attesters.contains(attester.key) {
+        msg!("only attesters allowed in program config can attest observations");
+        return Err(MdpError::Unauthorized.into());
+    }
+
+    load_record(record_account, &observation.identity)?;
+
+    let slot = Clock::get()?.slot;
+    let window = ATTESTATION_BUCKET_SLOTS * ATTESTATION_BUCKETS as u64;
+    if observation.slot > slot || observation.slot + window <= slot {
+        msg!(
+            "observation slot {} is outside of attestation window at slot {}",
+            observation.slot,
+            slot
+        );
+        return Err(MdpError::InvalidObservation.into());
+    }
+
+    let (pda, bump) = AttestationRecord::pda(&observation.identity);
+    let mut attestation = load_pda_if_exists(attestation_account, &pda)?
+        .unwrap_or_else(|| AttestationRecord::new(observation.identity));
+
+    if !attestation.record(&observation) {
+        msg!("observation is older than the rolling window of attestations");
+        return Err(MdpError::InvalidObservation.into());
+    }
+
+    let seeds = [ER_ATTESTATION_SEED, observation.identity.as_ref(), &[bump]];
+    store_pda(
+        &attestation,
+        attestation_account,
+        &seeds,
+        attester,
+        system_program,
+    )
+}
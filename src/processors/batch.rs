@@ -0,0 +1,82 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    msg,
+    program_error::ProgramError,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    consts::MAX_BATCH_SYNC, error::MdpError, instructions::sync::SyncInstruction,
+    state::index::IndexKey,
+};
+
+use super::{
+    config::load_config,
+    index::update_indexes,
+    sync::apply_sync,
+    utils::{load_record, store_record},
+};
+
+/// Synchronize multiple ER records, sharing the same authority, in one go,
+/// either all of the updates are applied or none of them, index pages affected
+/// by the updates follow the record PDAs
+pub fn process_batch_sync<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    batch: Vec<SyncInstruction>,
+) -> Result<(), ProgramError> {
+    let authority = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+    let config_account = next_account_info(&mut accounts)?;
+
+    if !authority.is_signer {
+        msg!("batch sync authority should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if batch.len() > MAX_BATCH_SYNC {
+        msg!(
+            "batch sync contains {} updates, at most {} are allowed",
+            batch.len(),
+            MAX_BATCH_SYNC
+        );
+        return Err(MdpError::BatchTooLarge.into());
+    }
+
+    let config = load_config(config_account)?;
+    let slot = Clock::get()?.slot;
+
+    let mut changes = Vec::with_capacity(batch.len());
+    for mut ix in batch {
+        let pda_account = next_account_info(&mut accounts)?;
+
+        let mut record = load_record(pda_account, ix.identity())?;
+
+        if record.authority() != authority.key {
+            msg!(
+                "batch sync signer is not the authority of {}",
+                ix.identity()
+            );
+            return Err(MdpError::Unauthorized.into());
+        }
+
+        let before = IndexKey::of(&record);
+        apply_sync(&mut record, &mut ix, &config, slot)?;
+
+        store_record(&record, pda_account, authority, system_program)?;
+        changes.push((*record.identity(), before, IndexKey::of(&record)));
+    }
+
+    let pages: Vec<_> = accounts.collect();
+    for (identity, before, after) in changes {
+        update_indexes(
+            &identity,
+            &before,
+            &after,
+            &pages,
+            authority,
+            system_program,
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable, msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    consts::{CONFIG_SEED, MAX_ATTESTERS},
+    error::MdpError,
+    state::config::Config,
+    ID,
+};
+
+use super::utils::{create_pda, store_record};
+
+/// Offset of the upgrade authority in program data account, i.e. the
+/// size of enum tag (u32) and the slot (u64), followed by option tag (u8)
+const UPGRADE_AUTHORITY_OFFSET: usize = 4 + 8 + 1;
+/// Size of the program data account metadata, preceding program's ELF
+const PROGRAM_DATA_METADATA_SIZE: usize = UPGRADE_AUTHORITY_OFFSET + 32;
+/// Enum tag of `UpgradeableLoaderState::ProgramData`
+const PROGRAM_DATA_TAG: u32 = 3;
+
+/// Initializes program configuration, only the upgrade authority
+/// of the program is allowed to do so, becoming its first admin
+pub fn process_init_config<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    config: Config,
+) -> Result<(), ProgramError> {
+    let payer = next_account_info(&mut accounts)?;
+    let config_account = next_account_info(&mut accounts)?;
+    let program_data = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+
+    if !payer.is_signer {
+        msg!("transaction payer should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (address, _) = Pubkey::find_program_address(&[ID.as_ref()], &bpf_loader_upgradeable::ID);
+    if address != *program_data.key || *program_data.owner != bpf_loader_upgradeable::ID {
+        msg!("invalid program data account {}", program_data.key);
+        return Err(MdpError::InvalidProgramData.into());
+    }
+    let data = program_data.try_borrow_data()?;
+    let authority = match data.get(..PROGRAM_DATA_METADATA_SIZE) {
+        Some(meta)
+            if meta[..4] == PROGRAM_DATA_TAG.to_le_bytes()
+                && meta[UPGRADE_AUTHORITY_OFFSET - 1] == 1 =>
+        {
+            Some(&meta[UPGRADE_AUTHORITY_OFFSET..])
+        }
+        _ => None,
+    };
+    if authority != Some(payer.key.as_ref()) {
+        msg!("only program upgrade authority can initialize the config");
+        return Err(MdpError::Unauthorized.into());
+    }
+
+    if config.admin != *payer.key {
+        msg!("config admin should be the upgrade authority");
+        return Err(MdpError::InvalidConfig.into());
+    }
+    validate_config(&config)?;
+
+    let (pda, bump) = Config::pda();
+    if pda != *config_account.key {
+        return Err(MdpError::InvalidPda.into());
+    }
+
+    create_pda(
+        &config,
+        config_account,
+        &[CONFIG_SEED, &[bump]],
+        payer,
+        system_program,
+    )
+}
+
+/// Replaces program configuration, including its admin, signed by the current admin,
+/// the account is resized to the new layout (admin covers any rent shortfall), so the
+/// config written by the earlier program version is migrated with this instruction
+pub fn process_update_config<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    config: Config,
+) -> Result<(), ProgramError> {
+    let admin = next_account_info(&mut accounts)?;
+    let config_account = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+
+    if !admin.is_signer {
+        msg!("config admin should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if load_config_admin(config_account)? != *admin.key {
+        msg!("transaction signer is not the config admin");
+        return Err(MdpError::Unauthorized.into());
+    }
+    validate_config(&config)?;
+
+    store_record(&config, config_account, admin, system_program)
+}
+
+/// Reads the admin of program configuration, which leads every layout of config, so
+/// that it can be replaced even if the rest of it cannot be decoded by this version
+fn load_config_admin(config_account: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    if *config_account.owner != ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    if Config::pda().0 != *config_account.key {
+        msg!("invalid program config account {}", config_account.key);
+        return Err(MdpError::InvalidPda.into());
+    }
+    let data = config_account.try_borrow_data()?;
+    Pubkey::try_from_slice(data.get(..32).unwrap_or_default())
+        .map_err(|_| ProgramError::InvalidAccountData)
+}
+
+/// Checks that the configuration is well formed, negative cooldown would
+/// let ER nodes complete their exit right away, escaping disputes, and the
+/// set of attesters is bounded
+fn validate_config(config: &Config) -> Result<(), ProgramError> {
+    if config.unbonding_cooldown_secs < 0 {
+        msg!(
+            "unbonding cooldown cannot be negative: {}",
+            config.unbonding_cooldown_secs
+        );
+        return Err(MdpError::InvalidConfig.into());
+    }
+    if config.attesters.len() > MAX_ATTESTERS {
+        msg!(
+            "config allows {} attesters, at most {} are allowed",
+            config.attesters.len(),
+            MAX_ATTESTERS
+        );
+        return Err(MdpError::InvalidConfig.into());
+    }
+    Ok(())
+}
+
+/// Loads program configuration from its PDA
+pub fn load_config(config_account: &AccountInfo) -> Result<Config, ProgramError> {
+    if *config_account.owner != ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    if Config::pda().0 != *config_account.key {
+        msg!("invalid program config account {}", config_account.key);
+        return Err(MdpError::InvalidPda.into());
+    }
+    let data = config_account.try_borrow_data()?;
+    Config::try_from_slice(&data).map_err(|_| ProgramError::InvalidAccountData)
+}
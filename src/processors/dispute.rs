@@ -0,0 +1,226 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    consts::{ER_BOND_SEED, ER_DISPUTE_SEED},
+    error::MdpError,
+    state::{
+        bond::BondRecord,
+        dispute::{DisputeClaim, DisputeRecord, DisputeState, Resolution, Ruling, SlashRecipient},
+    },
+};
+
+use super::{
+    config::load_config,
+    utils::{
+        check_pda, close_pda, create_pda, deposit, load_pda, load_pda_if_exists, load_record,
+        load_state, store_pda, store_record, withdraw,
+    },
+};
+
+/// Posts given amount of lamports to the bond of ER node, creating its vault on first use
+pub fn process_bond<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    lamports: u64,
+) -> Result<(), ProgramError> {
+    let identity = next_account_info(&mut accounts)?;
+    let bond_account = next_account_info(&mut accounts)?;
+    let record_account = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+
+    if !identity.is_signer {
+        msg!("ER node identity should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    load_record(record_account, identity.key)?;
+
+    let (pda, bump) = BondRecord::pda(identity.key);
+    let mut bond = load_pda_if_exists(bond_account, &pda)?.unwrap_or(BondRecord {
+        identity: *identity.key,
+        amount: 0,
+        locked: 0,
+        open_disputes: 0,
+    });
+    bond.amount = bond
+        .amount
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let seeds = [ER_BOND_SEED, identity.key.as_ref(), &[bump]];
+    store_pda(&bond, bond_account, &seeds, identity, system_program)?;
+    deposit(identity, bond_account, lamports, system_program)
+}
+
+/// Opens dispute against bonded ER node, the challenger posts counter bond,
+/// and the same amount of ER node's bond gets locked until the resolution
+pub fn process_open_dispute<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    claim: DisputeClaim,
+) -> Result<(), ProgramError> {
+    let challenger = next_account_info(&mut accounts)?;
+    let dispute_account = next_account_info(&mut accounts)?;
+    let bond_account = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+
+    if !challenger.is_signer {
+        msg!("challenger should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if *challenger.key == claim.identity || claim.stake == 0 {
+        msg!("dispute should be opened by third party with non zero counter bond");
+        return Err(MdpError::InvalidDisputeClaim.into());
+    }
+
+    let mut bond = load_bond(bond_account, &claim.identity)?;
+    if bond.available() < claim.stake {
+        msg!(
+            "ER node has {} lamports available in its bond, while {} are claimed",
+            bond.available(),
+            claim.stake
+        );
+        return Err(MdpError::InsufficientBond.into());
+    }
+    bond.locked = bond
+        .locked
+        .checked_add(claim.stake)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    bond.open_disputes = bond
+        .open_disputes
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    let (pda, bump) = DisputeRecord::pda(&claim.identity, challenger.key);
+    check_pda(dispute_account, &pda)?;
+    let dispute = DisputeRecord {
+        identity: claim.identity,
+        challenger: *challenger.key,
+        reason: claim.reason,
+        evidence: claim.evidence,
+        stake: claim.stake,
+        opened_slot: Clock::get()?.slot,
+        state: DisputeState::Open,
+    };
+    let seeds = [
+        ER_DISPUTE_SEED,
+        claim.identity.as_ref(),
+        challenger.key.as_ref(),
+        &[bump],
+    ];
+    create_pda(
+        &dispute,
+        dispute_account,
+        &seeds,
+        challenger,
+        system_program,
+    )?;
+    deposit(challenger, dispute_account, claim.stake, system_program)?;
+
+    store_record(&bond, bond_account, challenger, system_program)
+}
+
+/// Resolves open dispute, signed by the admin or the arbiter, the losing side's stake
+/// is slashed to the winner or to the treasury, as decided by the ruling
+pub fn process_resolve_dispute<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    ruling: Ruling,
+) -> Result<(), ProgramError> {
+    let resolver = next_account_info(&mut accounts)?;
+    let config_account = next_account_info(&mut accounts)?;
+    let dispute_account = next_account_info(&mut accounts)?;
+    let bond_account = next_account_info(&mut accounts)?;
+    let challenger = next_account_info(&mut accounts)?;
+    let treasury = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+
+    if !resolver.is_signer {
+        msg!("dispute resolver should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(config_account)?;
+    if *resolver.key != config.admin && *resolver.key != config.arbiter {
+        msg!("only admin or arbiter can resolve disputes");
+        return Err(MdpError::Unauthorized.into());
+    }
+    if *treasury.key != config.treasury {
+        msg!(
+            "treasury doesn't match the configured one {}",
+            config.treasury
+        );
+        return Err(MdpError::TreasuryMismatch.into());
+    }
+
+    // dispute PDA is derived from its own state, so it's checked once loaded
+    let mut dispute: DisputeRecord = load_state(dispute_account)?;
+    check_pda(
+        dispute_account,
+        &DisputeRecord::pda(&dispute.identity, &dispute.challenger).0,
+    )?;
+    if dispute.challenger != *challenger.key {
+        msg!(
+            "challenger doesn't match the one of dispute {}",
+            dispute.challenger
+        );
+        return Err(MdpError::ChallengerMismatch.into());
+    }
+    let mut bond = load_bond(bond_account, &dispute.identity)?;
+
+    let recipient = match (ruling.upheld, ruling.slash_to) {
+        (_, SlashRecipient::Treasury) => treasury,
+        (true, SlashRecipient::Winner) => challenger,
+        (false, SlashRecipient::Winner) => bond_account,
+    };
+    let resolution = Resolution {
+        slot: Clock::get()?.slot,
+        slashed_to: *recipient.key,
+    };
+    dispute.resolve(&ruling, resolution)?;
+    msg!(
+        "dispute against {} by {} resolved: {:?}",
+        dispute.identity,
+        dispute.challenger,
+        dispute.state
+    );
+
+    let overflow = || ProgramError::ArithmeticOverflow;
+    bond.locked = bond
+        .locked
+        .checked_sub(dispute.stake)
+        .ok_or_else(overflow)?;
+    bond.open_disputes = bond.open_disputes.checked_sub(1).ok_or_else(overflow)?;
+    if ruling.upheld {
+        bond.amount = bond
+            .amount
+            .checked_sub(dispute.stake)
+            .ok_or_else(overflow)?;
+        withdraw(bond_account, recipient, dispute.stake)?;
+    } else {
+        if recipient.key == bond_account.key {
+            bond.amount = bond
+                .amount
+                .checked_add(dispute.stake)
+                .ok_or_else(overflow)?;
+        }
+        withdraw(dispute_account, recipient, dispute.stake)?;
+    }
+    store_record(&bond, bond_account, resolver, system_program)?;
+
+    // challenger gets back the rent, along with the counter bond if the dispute is upheld,
+    // closing the dispute allows the same challenger to open another one later
+    close_pda(dispute_account, challenger)
+}
+
+/// Loads bond record of given ER node from its vault
+pub fn load_bond(
+    bond_account: &AccountInfo,
+    identity: &Pubkey,
+) -> Result<BondRecord, ProgramError> {
+    load_pda(bond_account, &BondRecord::pda(identity).0)
+}
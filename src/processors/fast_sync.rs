@@ -0,0 +1,511 @@
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    consts::ER_RECORD_SEED,
+    instructions::sync,
+    state::{config::Config, record::V1_TAG, reputation::Heartbeats, status::ErStatus},
+    ID,
+};
+
+/// Index of `Instruction::Sync` in its Borsh encoding
+const SYNC_TAG: u8 = 2;
+
+/// Borsh encoded size of `LoadMetrics` and `SemVer`
+const METRICS_LEN: usize = 18;
+const SEMVER_LEN: usize = 6;
+
+/// Offsets of the fixed size fields in Borsh encoded `ErRecord::V1`
+const IDENTITY: usize = 1;
+const STATUS: usize = IDENTITY + 32 * 3 + 8;
+const BLOCK_TIME_MS: usize = STATUS + 1;
+const LOAD_AVERAGE: usize = BLOCK_TIME_MS + 2 + 32;
+const METRICS: usize = LOAD_AVERAGE + 4;
+const SOFTWARE_VERSION: usize = METRICS + METRICS_LEN + 3;
+const PROTOCOL_VERSION: usize = SOFTWARE_VERSION + SEMVER_LEN;
+const SEQUENCE: usize = PROTOCOL_VERSION + SEMVER_LEN;
+const HEARTBEATS: usize = SEQUENCE + 8 + 8;
+/// Offset of the variable size section, starting with fee schedule
+const FEES: usize = HEARTBEATS + 8 + 4;
+
+/// Fields of sync instruction, which can be applied to the record in place
+struct FastSync<'d> {
+    identity: &'d [u8; 32],
+    sequence: u64,
+    status: Option<&'d [u8; 1]>,
+    block_time_ms: Option<&'d [u8; 2]>,
+    load_average: Option<&'d [u8; 4]>,
+    metrics: Option<&'d [u8; METRICS_LEN]>,
+    software_version: Option<&'d [u8; SEMVER_LEN]>,
+    protocol_version: Option<&'d [u8; SEMVER_LEN]>,
+}
+
+/// Cursor over Borsh encoded bytes, reading them in place
+struct Reader<'d> {
+    data: &'d [u8],
+    offset: usize,
+}
+
+impl<'d> Reader<'d> {
+    fn new(data: &'d [u8], offset: usize) -> Self {
+        Self { data, offset }
+    }
+
+    fn take<const N: usize>(&mut self) -> Option<&'d [u8; N]> {
+        let bytes = self.data.get(self.offset..self.offset + N)?;
+        self.offset += N;
+        bytes.try_into().ok()
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take().copied().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take().copied().map(u64::from_le_bytes)
+    }
+
+    fn option<const N: usize>(&mut self) -> Option<Option<&'d [u8; N]>> {
+        match self.take::<1>()? {
+            [0] => Some(None),
+            [1] => self.take().map(Some),
+            _ => None,
+        }
+    }
+
+    /// Reads option tag, which should be `None` for the fast path to apply
+    fn none(&mut self) -> Option<()> {
+        (self.take::<1>()? == &[0]).then_some(())
+    }
+
+    /// Skips Borsh encoded `FeeSchedule`
+    fn skip_fees(&mut self) -> Option<()> {
+        self.take::<16>()?;
+        let surcharges = self.u32()? as usize;
+        self.offset = self.offset.checked_add(surcharges.checked_mul(1 + 8)?)?;
+        self.option::<2>()?;
+        Some(())
+    }
+}
+
+impl<'d> FastSync<'d> {
+    /// Parses sync instruction, provided that it only updates the fixed size fields
+    /// of the record, which don't take part in any of the secondary indexes
+    fn parse(data: &'d [u8]) -> Option<Self> {
+        let mut reader = Reader::new(data, 0);
+        if reader.take()? != &[SYNC_TAG, sync::V1_TAG] {
+            return None;
+        }
+        let identity = reader.take()?;
+        let sequence = reader.u64()?;
+        let status = reader.option()?;
+        let block_time_ms = reader.option()?;
+        // features
+        reader.none()?;
+        let load_average = reader.option()?;
+        let metrics = reader.option()?;
+        // country code, region and location
+        reader.none()?;
+        reader.none()?;
+        reader.none()?;
+        let software_version = reader.option()?;
+        let protocol_version = reader.option()?;
+        // fees, address, TLS fingerprint and maintenance windows
+        for _ in 0..4 {
+            reader.none()?;
+        }
+        (reader.offset == data.len()).then_some(Self {
+            identity,
+            sequence,
+            status,
+            block_time_ms,
+            load_average,
+            metrics,
+            software_version,
+            protocol_version,
+        })
+    }
+}
+
+/// Applies sync instruction directly to the serialized record, without decoding it and
+/// without any heap allocations, provided that the sync only updates the fixed size fields
+/// of the record, and that no pending fee increase has to be settled, otherwise (as well as
+/// in case of any error) returns `None`, so that the regular sync processor takes over,
+/// program config is only checked to be the initialized config PDA, its contents aren't
+/// read, as they only affect fee announcements, while system program is left unchecked,
+/// as the record is never resized, so no lamports are transferred
+pub fn try_process_fast_sync(accounts: &[AccountInfo], data: &[u8]) -> Option<ProgramResult> {
+    let ix = FastSync::parse(data)?;
+    let [payer, pda_account, _system_program, config, ..] = accounts else {
+        return None;
+    };
+    if !payer.is_signer || payer.key.as_ref() != ix.identity || *pda_account.owner != ID {
+        return None;
+    }
+    if *config.owner != ID || config.data_is_empty() || *config.key != Config::pda().0 {
+        return None;
+    }
+    let (pda, _) = Pubkey::find_program_address(&[ER_RECORD_SEED, ix.identity], &ID);
+    if pda != *pda_account.key {
+        return None;
+    }
+
+    let mut record = pda_account.try_borrow_mut_data().ok()?;
+    if record.first() != Some(&V1_TAG) || record.get(IDENTITY..IDENTITY + 32)? != ix.identity {
+        return None;
+    }
+
+    let mut reader = Reader::new(&record, SEQUENCE);
+    if ix.sequence <= reader.u64()? {
+        return None;
+    }
+    let deregistering = record[STATUS] == ErStatus::Deregistering as u8;
+    if let Some(status) = ix.status {
+        let status = ErStatus::try_from_slice(status).ok()?;
+        if deregistering != (status == ErStatus::Deregistering) {
+            return None;
+        }
+    }
+
+    let slot = Clock::get().ok()?.slot;
+    let mut reader = Reader::new(&record, FEES);
+    reader.skip_fees()?;
+    if reader.take::<1>()? == &[1] {
+        reader.skip_fees()?;
+        if reader.u64()? <= slot {
+            return None;
+        }
+    }
+
+    let mut reader = Reader::new(&record, HEARTBEATS);
+    let mut heartbeats = Heartbeats {
+        latest: reader.u64()?,
+        bitmap: reader.u32()?,
+    };
+    heartbeats.record(slot);
+
+    let mut write = |offset: usize, bytes: &[u8]| {
+        record[offset..offset + bytes.len()].copy_from_slice(bytes);
+    };
+    write(SEQUENCE, &ix.sequence.to_le_bytes());
+    write(HEARTBEATS, &heartbeats.latest.to_le_bytes());
+    write(HEARTBEATS + 8, &heartbeats.bitmap.to_le_bytes());
+    let fields: [(usize, Option<&[u8]>); 6] = [
+        (STATUS, ix.status.map(|s| &s[..])),
+        (BLOCK_TIME_MS, ix.block_time_ms.map(|b| &b[..])),
+        (LOAD_AVERAGE, ix.load_average.map(|l| &l[..])),
+        (METRICS, ix.metrics.map(|m| &m[..])),
+        (SOFTWARE_VERSION, ix.software_version.map(|v| &v[..])),
+        (PROTOCOL_VERSION, ix.protocol_version.map(|v| &v[..])),
+    ];
+    for (offset, bytes) in fields {
+        if let Some(bytes) = bytes {
+            write(offset, bytes);
+        }
+    }
+    Some(Ok(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{option, prelude::*, sample::select};
+    use solana_program::{
+        entrypoint::SUCCESS,
+        hash::Hash,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+    };
+
+    use super::*;
+    use crate::{
+        instructions::{sync::SyncInstruction, version::v1::SyncRecordV1, Instruction},
+        processors::sync::apply_sync,
+        state::{
+            features::FeaturesSet,
+            fees::{FeeSchedule, PendingFees},
+            metrics::LoadMetrics,
+            record::{CountryCode, ErRecord},
+            semver::SemVer,
+            version::v1::RecordV1,
+        },
+    };
+
+    /// Slot, which the clock sysvar reports
+    const SLOT: u64 = 1_000_000;
+
+    /// Stubbed runtime, reporting the clock at [SLOT]
+    struct ClockStub;
+
+    impl SyscallStubs for ClockStub {
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock {
+                slot: SLOT,
+                ..Default::default()
+            };
+            unsafe { (var_addr as *mut Clock).write_unaligned(clock) };
+            SUCCESS
+        }
+    }
+
+    fn status() -> impl Strategy<Value = ErStatus> {
+        select(vec![
+            ErStatus::Active,
+            ErStatus::Draining,
+            ErStatus::Offline,
+            ErStatus::Deregistering,
+        ])
+    }
+
+    fn metrics() -> impl Strategy<Value = LoadMetrics> {
+        any::<(u32, u16, u16, u32, u32, u16)>().prop_map(|m| LoadMetrics {
+            tps: m.0,
+            latency_p50_ms: m.1,
+            latency_p99_ms: m.2,
+            active_delegations: m.3,
+            max_delegations: m.4,
+            memory_pressure_bps: m.5,
+        })
+    }
+
+    fn semver() -> impl Strategy<Value = SemVer> {
+        any::<(u16, u16, u16)>().prop_map(|(major, minor, patch)| SemVer::new(major, minor, patch))
+    }
+
+    prop_compose! {
+        fn record(identity: Pubkey)(
+            (status, block_time_ms, load_average, metrics) in
+                (status(), any::<u16>(), any::<u32>(), metrics()),
+            (sequence, latest, bitmap) in (0..8u64, SLOT / 512..SLOT / 256, any::<u32>()),
+            pending in option::of((any::<u64>(), SLOT - 8..SLOT + 8)),
+            addr in "[a-z:/.]{0,32}",
+        ) -> ErRecord {
+            ErRecord::V1(RecordV1 {
+                identity,
+                authority: identity,
+                cluster: Hash::default(),
+                chain_id: 0,
+                status,
+                block_time_ms,
+                features: FeaturesSet::default(),
+                load_average,
+                metrics,
+                country_code: CountryCode::from("083"),
+                software_version: SemVer::default(),
+                protocol_version: SemVer::default(),
+                sequence,
+                registered_slot: 0,
+                heartbeats: Heartbeats { latest, bitmap },
+                fees: FeeSchedule::default(),
+                pending_fees: pending.map(|(lamports_per_signature, effective_slot)| {
+                    PendingFees {
+                        fees: FeeSchedule {
+                            lamports_per_signature,
+                            ..Default::default()
+                        },
+                        effective_slot,
+                    }
+                }),
+                exit_after: None,
+                tee: None,
+                tls_fingerprint: None,
+                region: None,
+                location: None,
+                addr,
+                maintenance: vec![],
+            })
+        }
+    }
+
+    prop_compose! {
+        fn sync(identity: Pubkey)(
+            (sequence, status, block_time_ms, load_average) in (
+                0..8u64,
+                option::of(status()),
+                option::of(any::<u16>()),
+                option::of(any::<u32>()),
+            ),
+            (metrics, software_version, protocol_version) in
+                (option::of(metrics()), option::of(semver()), option::of(semver())),
+            // fields, which are out of the fast path's reach
+            (addr, with_features) in (option::of("[a-z:/.]{0,32}"), prop::bool::weighted(0.1)),
+        ) -> SyncRecordV1 {
+            SyncRecordV1 {
+                identity,
+                sequence,
+                status,
+                block_time_ms,
+                features: with_features.then(FeaturesSet::default),
+                load_average,
+                metrics,
+                country_code: None,
+                region: None,
+                location: None,
+                software_version,
+                protocol_version,
+                fees: None,
+                addr,
+                tls_fingerprint: None,
+                maintenance: None,
+            }
+        }
+    }
+
+    fn record_and_sync() -> impl Strategy<Value = (ErRecord, SyncRecordV1)> {
+        any::<[u8; 32]>()
+            .prop_map(Pubkey::new_from_array)
+            .prop_flat_map(|identity| (record(identity), sync(identity)))
+    }
+
+    #[test]
+    fn test_record_layout() {
+        let metrics = LoadMetrics {
+            tps: 1,
+            latency_p50_ms: 2,
+            latency_p99_ms: 3,
+            active_delegations: 4,
+            max_delegations: 5,
+            memory_pressure_bps: 6,
+        };
+        let record = ErRecord::V1(RecordV1 {
+            identity: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            cluster: Hash::new_unique(),
+            chain_id: 7,
+            status: ErStatus::Offline,
+            block_time_ms: 8,
+            features: FeaturesSet::default(),
+            load_average: 9,
+            metrics,
+            country_code: CountryCode::from("083"),
+            software_version: SemVer::new(10, 11, 12),
+            protocol_version: SemVer::new(13, 14, 15),
+            sequence: 16,
+            registered_slot: 17,
+            heartbeats: Heartbeats {
+                latest: 18,
+                bitmap: 19,
+            },
+            fees: FeeSchedule::default(),
+            pending_fees: Some(PendingFees {
+                fees: FeeSchedule::default(),
+                effective_slot: 20,
+            }),
+            exit_after: None,
+            tee: None,
+            tls_fingerprint: None,
+            region: None,
+            location: None,
+            addr: String::new(),
+            maintenance: vec![],
+        });
+        let data = borsh::to_vec(&record).unwrap();
+        let at = |offset: usize, len: usize| &data[offset..offset + len];
+        assert_eq!(at(IDENTITY, 32), record.identity().as_ref());
+        assert_eq!(data[STATUS], ErStatus::Offline as u8);
+        assert_eq!(at(BLOCK_TIME_MS, 2), 8u16.to_le_bytes());
+        assert_eq!(at(LOAD_AVERAGE, 4), 9u32.to_le_bytes());
+        assert_eq!(at(METRICS, METRICS_LEN), borsh::to_vec(&metrics).unwrap());
+        assert_eq!(at(SOFTWARE_VERSION, 2), 10u16.to_le_bytes());
+        assert_eq!(at(PROTOCOL_VERSION, 2), 13u16.to_le_bytes());
+        assert_eq!(at(SEQUENCE, 8), 16u64.to_le_bytes());
+        assert_eq!(at(HEARTBEATS, 8), 18u64.to_le_bytes());
+        assert_eq!(at(HEARTBEATS + 8, 4), 19u32.to_le_bytes());
+        let mut reader = Reader::new(&data, FEES);
+        reader.skip_fees().unwrap();
+        assert_eq!(reader.take::<1>(), Some(&[1]));
+        reader.skip_fees().unwrap();
+        assert_eq!(reader.u64(), Some(20));
+    }
+
+    #[test]
+    fn test_parse_sync() {
+        let mut sync = SyncRecordV1 {
+            identity: Pubkey::new_unique(),
+            sequence: 3,
+            status: Some(ErStatus::Draining),
+            block_time_ms: None,
+            features: None,
+            load_average: Some(1_000),
+            metrics: None,
+            country_code: None,
+            region: None,
+            location: None,
+            software_version: None,
+            protocol_version: Some(SemVer::new(1, 2, 3)),
+            fees: None,
+            addr: None,
+            tls_fingerprint: None,
+            maintenance: None,
+        };
+        let data = borsh::to_vec(&Instruction::Sync(SyncInstruction::V1(sync))).unwrap();
+        let ix = FastSync::parse(&data).expect("status only sync should take fast path");
+        assert_eq!(ix.sequence, 3);
+        assert_eq!(ix.status, Some(&[ErStatus::Draining as u8]));
+        assert_eq!(ix.load_average, Some(&1_000u32.to_le_bytes()));
+        assert!(ix.block_time_ms.is_none());
+        assert!(ix.protocol_version.is_some());
+        assert!(FastSync::parse(&data[..data.len() - 1]).is_none());
+
+        sync = SyncRecordV1::try_from_slice(&data[2..]).unwrap();
+        sync.addr = Some("https://127.0.0.1:9324".to_string());
+        let data = borsh::to_vec(&Instruction::Sync(SyncInstruction::V1(sync))).unwrap();
+        assert!(FastSync::parse(&data).is_none());
+    }
+
+    proptest! {
+        #[test]
+        fn test_fast_sync_matches_regular((record, sync) in record_and_sync()) {
+            set_syscall_stubs(Box::new(ClockStub));
+            let data = borsh::to_vec(&Instruction::Sync(SyncInstruction::V1(sync))).unwrap();
+            let Ok(Instruction::Sync(mut ix)) = Instruction::try_from_slice(&data) else {
+                unreachable!("sync instruction should decode");
+            };
+            let config = Config {
+                admin: Pubkey::default(),
+                cluster: Hash::default(),
+                fee_notice_slots: 0,
+                arbiter: Pubkey::default(),
+                attesters: vec![],
+                treasury: Pubkey::default(),
+                unbonding_cooldown_secs: 0,
+            };
+            let before = borsh::to_vec(&record).unwrap();
+            let mut expected = record;
+            let regular = apply_sync(&mut expected, &mut ix, &config, SLOT);
+
+            let identity = *expected.identity();
+            let (pda, _) = expected.pda();
+            let (config_pda, _) = Config::pda();
+            let mut lamports = [1; 4];
+            let [payer_lamports, pda_lamports, system_lamports, config_lamports] = &mut lamports;
+            let mut pda_data = before.clone();
+            let mut config_data = borsh::to_vec(&config).unwrap();
+            let system = Pubkey::default();
+            let accounts = [
+                AccountInfo::new(&identity, true, true, payer_lamports, &mut [], &system, false, 0),
+                AccountInfo::new(&pda, false, true, pda_lamports, &mut pda_data, &ID, false, 0),
+                AccountInfo::new(&system, false, false, system_lamports, &mut [], &system, true, 0),
+                AccountInfo::new(
+                    &config_pda, false, false, config_lamports, &mut config_data, &ID, false, 0,
+                ),
+            ];
+            let mut forged = accounts.clone();
+            forged[3].key = &system;
+            prop_assert!(try_process_fast_sync(&forged, &data).is_none());
+            let fast = try_process_fast_sync(&accounts, &data);
+            let stored = accounts[1].data.borrow().to_vec();
+
+            match fast {
+                // fast path only takes the syncs, which the regular one applies the same way
+                Some(result) => {
+                    prop_assert!(result.is_ok() && regular.is_ok());
+                    prop_assert_eq!(stored, borsh::to_vec(&expected).unwrap());
+                }
+                // otherwise the record is left for the regular path untouched
+                None => prop_assert_eq!(stored, before),
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use borsh::BorshDeserialize;
+use solana_program::{account_info::AccountInfo, msg, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{
+    consts::ER_INDEX_SEED,
+    error::MdpError,
+    state::index::{IndexKey, IndexPage},
+    ID,
+};
+
+use super::utils::{create_pda, store_record};
+
+/// Moves identity of ER node between secondary indexes, according to the change of its
+/// indexed fields from `before` to `after` keys, affected index pages are looked up
+/// among the given accounts by address, and created on first use (paid for by payer)
+pub fn update_indexes<'a>(
+    identity: &Pubkey,
+    before: &[IndexKey],
+    after: &[IndexKey],
+    pages: &[&'a AccountInfo<'a>],
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<(), ProgramError> {
+    let page = IndexPage::of(identity);
+    for key in before.iter().filter(|k| !after.contains(k)) {
+        let (account, _) = find_page(key, page, pages)?;
+        let mut index = load_page(account)?;
+        index.remove(identity);
+        store_record(&index, account, payer, system_program)?;
+    }
+    for key in after.iter().filter(|k| !before.contains(k)) {
+        let (account, bump) = find_page(key, page, pages)?;
+        if account.lamports() == 0 {
+            let mut index = IndexPage::new(*key, page);
+            index.insert(*identity);
+            let seeds = [ER_INDEX_SEED, &key.seed(), &[page], &[bump]];
+            create_pda(&index, account, &seeds, payer, system_program)?;
+            continue;
+        }
+        let mut index = load_page(account)?;
+        index.insert(*identity);
+        store_record(&index, account, payer, system_program)?;
+    }
+    Ok(())
+}
+
+fn find_page<'a>(
+    key: &IndexKey,
+    page: u8,
+    pages: &[&'a AccountInfo<'a>],
+) -> Result<(&'a AccountInfo<'a>, u8), ProgramError> {
+    let (pda, bump) = IndexPage::pda(key, page);
+    let Some(account) = pages.iter().find(|a| *a.key == pda) else {
+        msg!(
+            "index page {} of {:?} should be passed to instruction",
+            page,
+            key
+        );
+        return Err(MdpError::MissingIndexPage.into());
+    };
+    Ok((account, bump))
+}
+
+fn load_page(account: &AccountInfo) -> Result<IndexPage, ProgramError> {
+    if *account.owner != ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let data = account.try_borrow_data()?;
+    IndexPage::try_from_slice(&data).map_err(|_| ProgramError::InvalidAccountData)
+}
@@ -1,3 +1,15 @@
+pub mod attest;
+pub mod batch;
+pub mod config;
+pub mod dispute;
+#[cfg_attr(not(feature = "zero-copy"), allow(dead_code))]
+pub mod fast_sync;
+pub mod index;
+pub mod name;
 pub mod register;
+pub mod relay;
+pub mod reputation;
 pub mod sync;
+pub mod tee;
 pub mod unregister;
+pub mod utils;
@@ -0,0 +1,173 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    consts::{ER_NAME_SEED, ER_REVERSE_NAME_SEED},
+    error::MdpError,
+    state::name::{name_hash, normalize, parent_name, NameRecord, ReverseNameRecord},
+    ID,
+};
+
+use super::utils::{check_pda, close_pda, create_pda, load_pda, load_record, store_record};
+
+/// Claims human readable name, creating the name record (which guarantees uniqueness).
+///
+/// Child names (e.g. `node1.acme`) should be authorized by the owner of the parent
+/// name (`acme`), whose record and signature follow the system program. If the name
+/// resolves to ER node, its identity signature, reverse lookup record and ER record
+/// are passed afterwards, the reverse lookup record is created as well.
+pub fn process_claim_name<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    name: String,
+    identity: Option<Pubkey>,
+) -> Result<(), ProgramError> {
+    let owner = next_account_info(&mut accounts)?;
+    let name_account = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+
+    if !owner.is_signer {
+        msg!("name owner should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if normalize(&name).as_ref() != Some(&name) {
+        msg!("name {} is not in normalized form", name);
+        return Err(MdpError::InvalidName.into());
+    }
+
+    let hash = name_hash(&name);
+    let (pda, name_bump) = NameRecord::pda(&name);
+    check_pda(name_account, &pda)?;
+
+    let parent = match parent_name(&name) {
+        Some(parent) => {
+            let parent_account = next_account_info(&mut accounts)?;
+            let parent_owner = next_account_info(&mut accounts)?;
+            let mut record = load_owned_name(parent_owner, parent_account, parent)?;
+            record.children = record
+                .children
+                .checked_add(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            store_record(&record, parent_account, owner, system_program)?;
+            Some(*parent_account.key)
+        }
+        None => None,
+    };
+
+    if let Some(identity) = identity {
+        let identity_account = next_account_info(&mut accounts)?;
+        let reverse_account = next_account_info(&mut accounts)?;
+        let record_account = next_account_info(&mut accounts)?;
+
+        if !identity_account.is_signer || *identity_account.key != identity {
+            msg!("ER node identity should sign for the name resolving to it");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // ER node should be registered before claiming a name
+        load_record(record_account, &identity)?;
+
+        let (pda, reverse_bump) = ReverseNameRecord::pda(&identity);
+        check_pda(reverse_account, &pda)?;
+        let reverse = ReverseNameRecord { name: name.clone() };
+        create_pda(
+            &reverse,
+            reverse_account,
+            &[ER_REVERSE_NAME_SEED, identity.as_ref(), &[reverse_bump]],
+            owner,
+            system_program,
+        )?;
+    }
+
+    let record = NameRecord {
+        owner: *owner.key,
+        identity,
+        parent,
+        children: 0,
+        name,
+    };
+    create_pda(
+        &record,
+        name_account,
+        &[ER_NAME_SEED, &hash, &[name_bump]],
+        owner,
+        system_program,
+    )
+}
+
+/// Releases human readable name, closing both forward and reverse lookup records,
+/// and returning their rent to the name owner. Names with children cannot be released.
+///
+/// Parent name record follows the system program, for child names, and reverse lookup
+/// record is passed last, for names resolving to ER node.
+pub fn process_release_name<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    name: String,
+) -> Result<(), ProgramError> {
+    let owner = next_account_info(&mut accounts)?;
+    let name_account = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+
+    let record = load_owned_name(owner, name_account, &name)?;
+
+    if record.children != 0 {
+        msg!("name {} still has {} children", name, record.children);
+        return Err(MdpError::NameHasChildren.into());
+    }
+
+    if let Some(parent) = record.parent {
+        let parent_account = next_account_info(&mut accounts)?;
+        let mut parent: NameRecord = load_pda(parent_account, &parent)?;
+        parent.children = parent.children.saturating_sub(1);
+        store_record(&parent, parent_account, owner, system_program)?;
+    }
+
+    if let Some(identity) = record.identity {
+        let reverse_account = next_account_info(&mut accounts)?;
+        check_pda(reverse_account, &ReverseNameRecord::pda(&identity).0)?;
+        if *reverse_account.owner != ID {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        close_pda(reverse_account, owner)?;
+    }
+
+    close_pda(name_account, owner)
+}
+
+/// Transfers ownership of human readable name, the name keeps resolving to the same ER node
+pub fn process_transfer_name<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    name: String,
+    new_owner: Pubkey,
+) -> Result<(), ProgramError> {
+    let owner = next_account_info(&mut accounts)?;
+    let name_account = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+
+    let mut record = load_owned_name(owner, name_account, &name)?;
+    record.owner = new_owner;
+
+    store_record(&record, name_account, owner, system_program)
+}
+
+/// Loads the name record, making sure that it's signed for by its owner
+fn load_owned_name(
+    owner: &AccountInfo,
+    name_account: &AccountInfo,
+    name: &str,
+) -> Result<NameRecord, ProgramError> {
+    if !owner.is_signer {
+        msg!("name owner should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let record: NameRecord = load_pda(name_account, &NameRecord::pda(name).0)?;
+    if record.owner != *owner.key {
+        msg!("transaction signer doesn't own the name {}", name);
+        return Err(MdpError::Unauthorized.into());
+    }
+    Ok(record)
+}
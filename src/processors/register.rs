@@ -1,24 +1,33 @@
-use borsh::BorshSerialize;
 use solana_program::msg;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    program::invoke_signed,
+    clock::Clock,
     program_error::ProgramError,
-    rent::Rent,
-    system_instruction::create_account,
     sysvar::Sysvar,
 };
 
-use crate::{state::record::ErRecord, ID};
+use crate::{
+    error::MdpError,
+    state::{features::Feature, index::IndexKey, record::ErRecord, status::ErStatus},
+};
+
+use super::{
+    config::load_config,
+    index::update_indexes,
+    sync::{validate_fees, validate_location, validate_maintenance},
+    utils::create_pda,
+};
 
-/// Registers ER node in domain registry, by creating a record (PDA) with all the relevant ER information
+/// Registers ER node in domain registry, by creating a record (PDA) with all the relevant ER information,
+/// and lists it in secondary indexes, which pages follow the config account, see [IndexKey::of]
 pub fn process_registration<'a>(
     mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
-    record: ErRecord,
+    mut record: ErRecord,
 ) -> Result<(), ProgramError> {
     let payer = next_account_info(&mut accounts)?;
     let pda_account = next_account_info(&mut accounts)?;
     let system_program = next_account_info(&mut accounts)?;
+    let config_account = next_account_info(&mut accounts)?;
 
     if !payer.is_signer {
         msg!("transaction payer should be signer");
@@ -27,7 +36,7 @@ pub fn process_registration<'a>(
 
     if payer.key != record.identity() {
         msg!("transaction payer should be the same as ER node identity");
-        return Err(ProgramError::InvalidArgument);
+        return Err(MdpError::IdentityMismatch.into());
     }
 
     if pda_account.lamports() != 0 {
@@ -42,25 +51,50 @@ pub fn process_registration<'a>(
             pda,
             pda_account.key
         );
-        return Err(ProgramError::InvalidArgument);
+        return Err(MdpError::InvalidPda.into());
     }
 
-    let mut data = Vec::new();
-    record.serialize(&mut data)?;
+    if record.status() == ErStatus::Deregistering || record.exit_after().is_some() {
+        msg!("ER node cannot be registered as deregistering one");
+        return Err(MdpError::Deregistering.into());
+    }
 
-    let space = data.len();
-    let rent = Rent::get()?.minimum_balance(data.len());
-    let [s1, s2] = record.seeds();
+    validate_maintenance(record.maintenance())?;
+    validate_fees(record.fees())?;
+    validate_location(record.location())?;
+    if record.pending_fees().is_some() {
+        msg!("fee increase cannot be announced on registration");
+        return Err(MdpError::InvalidFees.into());
+    }
+    if record.tee().is_some() || record.features().contains(Feature::TeeAttestation) {
+        msg!("TEE attestation can only be set by publishing the quote");
+        return Err(MdpError::InvalidTeeQuote.into());
+    }
 
-    let create_pda_ix = create_account(payer.key, &pda, rent, space as u64, &ID);
-    invoke_signed(
-        &create_pda_ix,
-        &[payer.clone(), pda_account.clone(), system_program.clone()],
-        &[&[s1, s2, &[bump]]],
-    )?;
+    let config = load_config(config_account)?;
+    if *record.cluster() != config.cluster {
+        msg!(
+            "ER node settles to cluster {}, while registry is deployed to {}",
+            record.cluster(),
+            config.cluster
+        );
+        return Err(MdpError::ClusterMismatch.into());
+    }
 
-    let mut borrowed = pda_account.try_borrow_mut_data()?;
-    borrowed.copy_from_slice(&data);
+    // sync sequence and heartbeat history start from scratch, whatever the client put in
+    record.set_sequence(0);
+    record.set_registered_slot(Clock::get()?.slot);
+
+    let [s1, s2] = record.seeds();
+    create_pda(
+        &record,
+        pda_account,
+        &[s1, s2, &[bump]],
+        payer,
+        system_program,
+    )?;
 
-    Ok(())
+    let pages: Vec<_> = accounts.collect();
+    let keys = IndexKey::of(&record);
+    update_indexes(record.identity(), &[], &keys, &pages, payer, system_program)
 }
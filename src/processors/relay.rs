@@ -0,0 +1,71 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    ed25519_program, msg,
+    program_error::ProgramError,
+    sysvar::{instructions::get_instruction_relative, Sysvar},
+};
+
+use crate::{instructions::relay::RelayedSync, state::index::IndexKey};
+
+use super::{
+    config::load_config,
+    index::update_indexes,
+    sync::apply_sync,
+    utils::{load_record, store_record},
+};
+
+/// Synchronize ER information signed off-chain by ER node identity, the transaction
+/// can be submitted by any relayer, which pays for it, while the authenticity of the
+/// update is established via ed25519 precompile instruction preceding this one, index
+/// pages affected by the update follow the config account
+pub fn process_relayed_sync<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    mut ix: RelayedSync,
+) -> Result<(), ProgramError> {
+    let relayer = next_account_info(&mut accounts)?;
+    let pda_account = next_account_info(&mut accounts)?;
+    let instructions = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+    let config_account = next_account_info(&mut accounts)?;
+
+    if !relayer.is_signer {
+        msg!("transaction relayer should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut record = load_record(pda_account, ix.sync.identity())?;
+
+    let verify_ix = get_instruction_relative(-1, instructions)?;
+    if verify_ix.program_id != ed25519_program::ID {
+        msg!("relayed sync should be preceded by ed25519 signature verification");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (signer, message) = RelayedSync::parse_verify_instruction(&verify_ix.data)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if signer != *ix.sync.identity() {
+        msg!("relayed sync should be signed by ER node identity");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if message != ix.message() {
+        msg!("signed message doesn't match relayed sync");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let config = load_config(config_account)?;
+    let before = IndexKey::of(&record);
+    apply_sync(&mut record, &mut ix.sync, &config, Clock::get()?.slot)?;
+
+    store_record(&record, pda_account, relayer, system_program)?;
+
+    let pages: Vec<_> = accounts.collect();
+    let after = IndexKey::of(&record);
+    update_indexes(
+        record.identity(),
+        &before,
+        &after,
+        &pages,
+        relayer,
+        system_program,
+    )
+}
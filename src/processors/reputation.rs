@@ -0,0 +1,60 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    consts::ER_REPUTATION_SEED,
+    state::{
+        attestation::AttestationRecord,
+        reputation::{score, ReputationInputs, ReputationRecord},
+    },
+};
+
+use super::utils::{check_pda, load_pda_if_exists, load_record, store_pda};
+
+/// Permissionless crank, which recomputes reputation score of ER node out of its record and
+/// attestations, the reputation record is created on first use, paid for by the signer
+pub fn process_update_reputation<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    identity: Pubkey,
+) -> Result<(), ProgramError> {
+    let payer = next_account_info(&mut accounts)?;
+    let reputation_account = next_account_info(&mut accounts)?;
+    let record_account = next_account_info(&mut accounts)?;
+    let attestation_account = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+
+    if !payer.is_signer {
+        msg!("transaction payer should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let record = load_record(record_account, &identity)?;
+    // node might not have been attested yet
+    let attestation =
+        load_pda_if_exists(attestation_account, &AttestationRecord::pda(&identity).0)?;
+    let (pda, bump) = ReputationRecord::pda(&identity);
+    check_pda(reputation_account, &pda)?;
+
+    let slot = Clock::get()?.slot;
+    let inputs = ReputationInputs::collect(&record, attestation.as_ref(), slot);
+    let reputation = ReputationRecord {
+        identity,
+        score: score(&inputs),
+        slot,
+    };
+
+    let seeds = [ER_REPUTATION_SEED, identity.as_ref(), &[bump]];
+    store_pda(
+        &reputation,
+        reputation_account,
+        &seeds,
+        payer,
+        system_program,
+    )
+}
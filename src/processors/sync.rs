@@ -1,51 +1,142 @@
-use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    msg,
     program_error::ProgramError,
+    sysvar::Sysvar,
 };
 
-use crate::{instructions::sync::SyncInstruction, state::record::ErRecord, ID};
+use crate::{
+    consts::MAX_MAINTENANCE_WINDOWS,
+    error::MdpError,
+    instructions::sync::SyncInstruction,
+    state::{
+        config::Config, features::Feature, fees::FeeSchedule, geo::GeoPoint, index::IndexKey,
+        maintenance::MaintenanceWindow, record::ErRecord, status::ErStatus,
+    },
+};
+
+use super::{
+    config::load_config,
+    index::update_indexes,
+    utils::{load_record, store_record},
+};
 
-/// Synchronize updated ER information with existing domain registry record
+/// Synchronize updated ER information with existing domain registry record, index pages
+/// affected by the change of country or features follow the config account
 pub fn process_sync_record<'a>(
     mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
     mut ix: SyncInstruction,
 ) -> Result<(), ProgramError> {
     let payer = next_account_info(&mut accounts)?;
     let pda_account = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+    let config_account = next_account_info(&mut accounts)?;
 
-    if *pda_account.owner != ID {
-        return Err(ProgramError::InvalidAccountOwner);
+    if !payer.is_signer {
+        msg!("transaction payer should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if !(payer.is_signer && payer.key == ix.identity()) {
-        return Err(ProgramError::InvalidArgument);
+    if payer.key != ix.identity() {
+        msg!("transaction payer should be the same as ER node identity");
+        return Err(MdpError::IdentityMismatch.into());
     }
 
-    if pda_account.lamports() == 0 {
-        return Err(ProgramError::UninitializedAccount);
-    }
+    let mut record = load_record(pda_account, ix.identity())?;
 
-    let pda = ix.pda();
+    let config = load_config(config_account)?;
+    let before = IndexKey::of(&record);
+    apply_sync(&mut record, &mut ix, &config, Clock::get()?.slot)?;
 
-    if pda != *pda_account.key {
-        return Err(ProgramError::InvalidArgument);
+    store_record(&record, pda_account, payer, system_program)?;
+
+    let pages: Vec<_> = accounts.collect();
+    let after = IndexKey::of(&record);
+    update_indexes(
+        record.identity(),
+        &before,
+        &after,
+        &pages,
+        payer,
+        system_program,
+    )
+}
+
+/// Checks that announced maintenance windows are well formed
+pub fn validate_maintenance(maintenance: &[MaintenanceWindow]) -> Result<(), ProgramError> {
+    if maintenance.len() > MAX_MAINTENANCE_WINDOWS || !maintenance.iter().all(|w| w.is_valid()) {
+        msg!("invalid maintenance windows announced");
+        return Err(MdpError::InvalidMaintenance.into());
     }
+    Ok(())
+}
 
-    let mut data = pda_account.try_borrow_mut_data()?;
-    let mut record =
-        ErRecord::try_from_slice(&data).map_err(|_| ProgramError::InvalidAccountData)?;
+/// Checks that fee schedule is well formed
+pub fn validate_fees(fees: &FeeSchedule) -> Result<(), ProgramError> {
+    if !fees.is_valid() {
+        msg!("invalid fee schedule announced");
+        return Err(MdpError::InvalidFees.into());
+    }
+    Ok(())
+}
 
+/// Checks that announced coordinates are within their valid ranges
+pub fn validate_location(location: Option<&GeoPoint>) -> Result<(), ProgramError> {
+    if location.is_some_and(|l| !l.is_valid()) {
+        msg!("invalid geographic coordinates announced");
+        return Err(MdpError::InvalidLocation.into());
+    }
+    Ok(())
+}
+
+/// Applies all of the fields set in sync instruction to the given record at given slot,
+/// provided that the sync is newer than the last one applied to it, fee increases
+/// are held back for the notice period set in program config
+pub fn apply_sync(
+    record: &mut ErRecord,
+    ix: &mut SyncInstruction,
+    config: &Config,
+    slot: u64,
+) -> Result<(), ProgramError> {
+    if ix.sequence() <= record.sequence() {
+        msg!(
+            "sync sequence {} should be greater than the last applied {}",
+            ix.sequence(),
+            record.sequence()
+        );
+        return Err(MdpError::StaleSequence.into());
+    }
+    let deregistering = record.status() == ErStatus::Deregistering;
+    if ix
+        .status()
+        .is_some_and(|s| deregistering != (s == ErStatus::Deregistering))
+    {
+        msg!("deregistering status can only be set by starting the exit");
+        return Err(MdpError::Deregistering.into());
+    }
+    record.set_sequence(ix.sequence());
+    record.heartbeat(slot);
+    record.settle_fees(slot);
     if let Some(addr) = ix.addr().take() {
         record.set_addr(addr);
     }
-    if let Some(base_fee) = ix.base_fee().take() {
-        record.set_base_fee(base_fee);
+    if let Some(fingerprint) = ix.tls_fingerprint().take() {
+        record.set_tls_fingerprint(fingerprint);
+    }
+    if let Some(fees) = ix.fees().take() {
+        validate_fees(&fees)?;
+        record.announce_fees(fees, slot, config.fee_notice_slots);
     }
     if let Some(block_time_ms) = ix.block_time_ms().take() {
         record.set_block_time_ms(block_time_ms);
     }
     if let Some(features) = ix.features().take() {
+        let tee = Feature::TeeAttestation;
+        if features.contains(tee) != record.features().contains(tee) {
+            msg!("TEE attestation feature can only be set by publishing the quote");
+            return Err(MdpError::InvalidTeeQuote.into());
+        }
         record.set_features(features);
     }
     if let Some(status) = ix.status().take() {
@@ -54,8 +145,28 @@ pub fn process_sync_record<'a>(
     if let Some(load_average) = ix.load_average().take() {
         record.set_load_average(load_average);
     }
-
-    record.serialize(&mut *data)?;
-
+    if let Some(metrics) = ix.metrics().take() {
+        record.set_metrics(metrics);
+    }
+    if let Some(country_code) = ix.country_code().take() {
+        record.set_country_code(country_code);
+    }
+    if let Some(region) = ix.region().take() {
+        record.set_region(region);
+    }
+    if let Some(location) = ix.location().take() {
+        validate_location(location.as_ref())?;
+        record.set_location(location);
+    }
+    if let Some(version) = ix.software_version().take() {
+        record.set_software_version(version);
+    }
+    if let Some(version) = ix.protocol_version().take() {
+        record.set_protocol_version(version);
+    }
+    if let Some(maintenance) = ix.maintenance().take() {
+        validate_maintenance(&maintenance)?;
+        record.set_maintenance(maintenance);
+    }
     Ok(())
 }
@@ -0,0 +1,129 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    hash::Hash,
+    msg,
+    program_error::ProgramError,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    consts::{ER_TEE_QUOTE_SEED, MAX_TEE_QUOTE_LEN},
+    error::MdpError,
+    state::{
+        features::Feature,
+        index::IndexKey,
+        tee::{
+            measurement_hash, quote_hash, QuoteChunk, QuotePublication, QuoteType, TeeAttestation,
+            TeeQuoteRecord,
+        },
+    },
+};
+
+use super::{
+    index::update_indexes,
+    utils::{load_pda, load_pda_if_exists, load_record, store_pda, store_record},
+};
+
+/// Writes chunk of raw TEE attestation quote into quote record of the signing ER node,
+/// creating the record on first use, quotes exceed single transaction size, so they
+/// are uploaded in several chunks, before being published with [process_publish_tee_quote]
+pub fn process_write_tee_quote<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    chunk: QuoteChunk,
+) -> Result<(), ProgramError> {
+    let identity = next_account_info(&mut accounts)?;
+    let quote_account = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+
+    if !identity.is_signer {
+        msg!("ER node identity should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let len = chunk.len as usize;
+    let offset = chunk.offset as usize;
+    if len > MAX_TEE_QUOTE_LEN || offset + chunk.bytes.len() > len {
+        msg!(
+            "quote chunk at {} of {} bytes doesn't fit into quote of {} bytes",
+            offset,
+            chunk.bytes.len(),
+            len
+        );
+        return Err(MdpError::InvalidTeeQuote.into());
+    }
+
+    let (pda, bump) = TeeQuoteRecord::pda(identity.key);
+    let mut record = load_pda_if_exists(quote_account, &pda)?.unwrap_or(TeeQuoteRecord {
+        identity: *identity.key,
+        quote: Vec::new(),
+    });
+    record.quote.resize(len, 0);
+    record.quote[offset..offset + chunk.bytes.len()].copy_from_slice(&chunk.bytes);
+
+    let seeds = [ER_TEE_QUOTE_SEED, identity.key.as_ref(), &[bump]];
+    store_pda(&record, quote_account, &seeds, identity, system_program)
+}
+
+/// Publishes uploaded TEE quote into the record of the signing ER node, by storing its
+/// hash along with the parsed measurement, and advertising [Feature::TeeAttestation],
+/// which lists the node in the feature's index, its page follows the system program
+pub fn process_publish_tee_quote<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    publication: QuotePublication,
+) -> Result<(), ProgramError> {
+    let identity = next_account_info(&mut accounts)?;
+    let record_account = next_account_info(&mut accounts)?;
+    let quote_account = next_account_info(&mut accounts)?;
+    let system_program = next_account_info(&mut accounts)?;
+
+    if !identity.is_signer {
+        msg!("ER node identity should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let mut record = load_record(record_account, identity.key)?;
+    let quote =
+        load_pda::<TeeQuoteRecord>(quote_account, &TeeQuoteRecord::pda(identity.key).0)?.quote;
+
+    let measurement = match publication.quote_type {
+        QuoteType::Nitro => Some(Hash::default()),
+        ty => measurement_hash(ty, &quote),
+    };
+    let Some(measurement) = measurement else {
+        msg!(
+            "uploaded quote is not a valid {:?} quote",
+            publication.quote_type
+        );
+        return Err(MdpError::InvalidTeeQuote.into());
+    };
+
+    if publication.expires_at <= Clock::get()?.unix_timestamp {
+        msg!(
+            "attestation expiring at {} has already expired",
+            publication.expires_at
+        );
+        return Err(MdpError::InvalidTeeQuote.into());
+    }
+
+    record.set_tee(Some(TeeAttestation {
+        quote_type: publication.quote_type,
+        measurement,
+        quote_hash: quote_hash(&quote),
+        expires_at: publication.expires_at,
+    }));
+    let before = IndexKey::of(&record);
+    record.set_features(record.features().clone().activate(Feature::TeeAttestation));
+
+    store_record(&record, record_account, identity, system_program)?;
+
+    let pages: Vec<_> = accounts.collect();
+    let after = IndexKey::of(&record);
+    update_indexes(
+        identity.key,
+        &before,
+        &after,
+        &pages,
+        identity,
+        system_program,
+    )
+}
@@ -1,71 +1,110 @@
-use borsh::BorshDeserialize;
 use solana_program::msg;
 use solana_program::pubkey::Pubkey;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::Clock,
     program_error::ProgramError,
+    sysvar::Sysvar,
 };
 
+use crate::error::MdpError;
+use crate::state::bond::BondRecord;
+use crate::state::index::IndexKey;
 use crate::state::record::ErRecord;
-use crate::ID;
+use crate::state::status::ErStatus;
 
-/// Unregisters given ER node, by removing its record from domain registry
-pub fn process_unregistration<'a>(
+use super::config::load_config;
+use super::index::update_indexes;
+use super::utils::{close_pda, load_pda_if_exists, load_record, store_record};
+
+/// Starts the exit of given ER node from domain registry, by moving its record into
+/// deregistering state, which hides it from discovery, the exit can be completed
+/// once the unbonding cooldown set in program config passes, the node is removed from
+/// secondary indexes, which pages follow the config account
+pub fn process_begin_unregistration<'a>(
     mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
     node_id: Pubkey,
 ) -> Result<(), ProgramError> {
     let payer = next_account_info(&mut accounts)?;
     let pda_account = next_account_info(&mut accounts)?;
     let system_program = next_account_info(&mut accounts)?;
+    let config_account = next_account_info(&mut accounts)?;
 
-    if *pda_account.owner != ID {
-        return Err(ProgramError::InvalidAccountOwner);
+    let mut record = load_signed_record(payer, pda_account, node_id)?;
+    if record.status() == ErStatus::Deregistering {
+        msg!("ER node has already started its exit");
+        return Err(MdpError::Deregistering.into());
     }
 
-    if !payer.is_signer {
-        msg!("transaction payer should be signer");
-        return Err(ProgramError::MissingRequiredSignature);
-    }
-    if *payer.key != node_id {
-        msg!("transaction payer should be the same as ER node identity");
-        return Err(ProgramError::InvalidArgument);
-    }
+    let config = load_config(config_account)?;
+    let now = Clock::get()?.unix_timestamp;
+    let before = IndexKey::of(&record);
+    record.begin_exit(now.saturating_add(config.unbonding_cooldown_secs));
 
-    if pda_account.lamports() == 0 {
-        msg!("tried to unregister non-exsistent record PDA");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    let data = pda_account.try_borrow_data()?;
-    let record = ErRecord::try_from_slice(&data).map_err(|e| {
-        msg!(
-            "failed to deserialize record entry from slice for account {}: {}",
-            pda_account.key,
-            e
-        );
-        ProgramError::InvalidAccountData
-    })?;
+    store_record(&record, pda_account, payer, system_program)?;
 
-    drop(data);
+    let pages: Vec<_> = accounts.collect();
+    update_indexes(&node_id, &before, &[], &pages, payer, system_program)
+}
 
-    if node_id != *record.identity() {
-        return Err(ProgramError::InvalidArgument);
-    }
+/// Completes the exit of given ER node, by removing its record from domain registry
+/// and releasing its bond, provided that the cooldown has passed and there are no
+/// open disputes against the node
+///
+/// Reclaimed lamports are sent to the optional recipient account, which
+/// follows the system program in the accounts list, or to the payer otherwise
+pub fn process_complete_unregistration<'a>(
+    mut accounts: impl Iterator<Item = &'a AccountInfo<'a>>,
+    node_id: Pubkey,
+) -> Result<(), ProgramError> {
+    let payer = next_account_info(&mut accounts)?;
+    let pda_account = next_account_info(&mut accounts)?;
+    let bond_account = next_account_info(&mut accounts)?;
+    let _system_program = next_account_info(&mut accounts)?;
+    let recipient = next_account_info(&mut accounts).unwrap_or(payer);
 
-    let (pda, _) = record.pda();
+    let record = load_signed_record(payer, pda_account, node_id)?;
 
-    if pda != *pda_account.key {
-        return Err(ProgramError::InvalidArgument);
+    let now = Clock::get()?.unix_timestamp;
+    if record.status() != ErStatus::Deregistering || record.exit_after().is_none_or(|t| now < t) {
+        msg!("ER node exit hasn't been started, or its cooldown hasn't passed yet");
+        return Err(MdpError::ExitNotReady.into());
     }
 
-    let payer_balance = payer.lamports();
-    let pda_balance = pda_account.lamports();
-    **payer.try_borrow_mut_lamports()? = payer_balance
-        .checked_add(pda_balance)
-        .ok_or(ProgramError::ArithmeticOverflow)?;
-    **pda_account.try_borrow_mut_lamports()? = 0;
+    if recipient.key == pda_account.key || recipient.key == bond_account.key {
+        msg!("lamports recipient cannot be the record or the bond PDA itself");
+        return Err(MdpError::InvalidRecipient.into());
+    }
 
-    pda_account.assign(system_program.key);
-    pda_account.realloc(0, false)?;
+    // node might have never posted a bond
+    let bond = load_pda_if_exists::<BondRecord>(bond_account, &BondRecord::pda(&node_id).0)?;
+    if let Some(bond) = bond {
+        if bond.open_disputes != 0 {
+            msg!(
+                "ER node has {} open disputes against it",
+                bond.open_disputes
+            );
+            return Err(MdpError::DisputesPending.into());
+        }
+        close_pda(bond_account, recipient)?;
+    }
 
-    Ok(())
+    close_pda(pda_account, recipient)
+}
+
+/// Loads the record of given ER node, checking that the payer is its identity
+fn load_signed_record(
+    payer: &AccountInfo,
+    pda_account: &AccountInfo,
+    node_id: Pubkey,
+) -> Result<ErRecord, ProgramError> {
+    if !payer.is_signer {
+        msg!("transaction payer should be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *payer.key != node_id {
+        msg!("transaction payer should be the same as ER node identity");
+        return Err(MdpError::IdentityMismatch.into());
+    }
+    load_record(pda_account, &node_id)
 }
@@ -0,0 +1,201 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::{create_account, transfer},
+    system_program,
+    sysvar::Sysvar,
+};
+
+use crate::{consts::ER_RECORD_SEED, error::MdpError, state::record::ErRecord, ID};
+
+/// Creates program owned PDA, funded by payer, and writes serialized state into it
+pub fn create_pda<'a>(
+    state: &impl BorshSerialize,
+    pda_account: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<(), ProgramError> {
+    if pda_account.lamports() != 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let data = borsh::to_vec(state)?;
+    let rent = Rent::get()?.minimum_balance(data.len());
+
+    let create_pda_ix = create_account(payer.key, pda_account.key, rent, data.len() as u64, &ID);
+    invoke_signed(
+        &create_pda_ix,
+        &[payer.clone(), pda_account.clone(), system_program.clone()],
+        &[seeds],
+    )?;
+
+    pda_account.try_borrow_mut_data()?.copy_from_slice(&data);
+    Ok(())
+}
+
+/// Transfers lamports from payer (owned by system program) to the given account
+pub fn deposit<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    lamports: u64,
+    system_program: &AccountInfo<'a>,
+) -> Result<(), ProgramError> {
+    invoke(
+        &transfer(payer.key, account.key, lamports),
+        &[payer.clone(), account.clone(), system_program.clone()],
+    )
+}
+
+/// Moves lamports out of program owned PDA to arbitrary account
+pub fn withdraw(
+    pda_account: &AccountInfo,
+    recipient: &AccountInfo,
+    lamports: u64,
+) -> Result<(), ProgramError> {
+    let pda_balance = pda_account
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    let recipient_balance = recipient
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **pda_account.try_borrow_mut_lamports()? = pda_balance;
+    **recipient.try_borrow_mut_lamports()? = recipient_balance;
+    Ok(())
+}
+
+/// Closes program owned PDA, moving all of its lamports to recipient, the data is
+/// wiped before the account is handed back to system program, so that no stale
+/// state is left behind
+pub fn close_pda(pda_account: &AccountInfo, recipient: &AccountInfo) -> Result<(), ProgramError> {
+    let recipient_balance = recipient.lamports();
+    let pda_balance = pda_account.lamports();
+    **recipient.try_borrow_mut_lamports()? = recipient_balance
+        .checked_add(pda_balance)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **pda_account.try_borrow_mut_lamports()? = 0;
+
+    pda_account.try_borrow_mut_data()?.fill(0);
+    pda_account.assign(&system_program::ID);
+    pda_account.realloc(0, false)
+}
+
+/// Writes record into its PDA, resizing the account if the length of serialized
+/// record has changed, any rent shortfall is covered by the payer, while excess
+/// lamports are kept in PDA and returned on unregistration
+pub fn store_record<'a>(
+    record: &impl BorshSerialize,
+    pda_account: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<(), ProgramError> {
+    let data = borsh::to_vec(record)?;
+
+    if data.len() != pda_account.data_len() {
+        let rent = Rent::get()?.minimum_balance(data.len());
+        let shortfall = rent.saturating_sub(pda_account.lamports());
+        if shortfall != 0 {
+            invoke(
+                &transfer(payer.key, pda_account.key, shortfall),
+                &[payer.clone(), pda_account.clone(), system_program.clone()],
+            )?;
+        }
+        pda_account.realloc(data.len(), false)?;
+    }
+
+    pda_account.try_borrow_mut_data()?.copy_from_slice(&data);
+    Ok(())
+}
+
+/// Checks that the account is the given PDA
+pub fn check_pda(account: &AccountInfo, pda: &Pubkey) -> Result<(), ProgramError> {
+    if pda != account.key {
+        msg!(
+            "expected PDA {} doesn't match provided one {}",
+            pda,
+            account.key
+        );
+        return Err(MdpError::InvalidPda.into());
+    }
+    Ok(())
+}
+
+/// Loads program owned state from the account, checking that it exists and is owned by
+/// the program, the address of the account has to be checked by the caller, see [load_pda]
+pub fn load_state<T: BorshDeserialize>(account: &AccountInfo) -> Result<T, ProgramError> {
+    if account.lamports() == 0 {
+        msg!("account {} doesn't exist", account.key);
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if *account.owner != ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let data = account.try_borrow_data()?;
+    T::try_from_slice(&data).map_err(|e| {
+        msg!(
+            "failed to deserialize state of account {}: {}",
+            account.key,
+            e
+        );
+        ProgramError::InvalidAccountData
+    })
+}
+
+/// Loads program owned state from the given PDA, see [load_state]
+pub fn load_pda<T: BorshDeserialize>(
+    account: &AccountInfo,
+    pda: &Pubkey,
+) -> Result<T, ProgramError> {
+    check_pda(account, pda)?;
+    load_state(account)
+}
+
+/// Same as [load_pda], but returns `None` if the PDA hasn't been created yet
+pub fn load_pda_if_exists<T: BorshDeserialize>(
+    account: &AccountInfo,
+    pda: &Pubkey,
+) -> Result<Option<T>, ProgramError> {
+    check_pda(account, pda)?;
+    if account.lamports() == 0 {
+        return Ok(None);
+    }
+    load_state(account).map(Some)
+}
+
+/// Writes state into its PDA, creating the account on first use, see [create_pda]
+/// and [store_record], in both cases the rent is paid for by the payer
+pub fn store_pda<'a>(
+    state: &impl BorshSerialize,
+    pda_account: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<(), ProgramError> {
+    if pda_account.lamports() == 0 {
+        return create_pda(state, pda_account, seeds, payer, system_program);
+    }
+    if *pda_account.owner != ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    store_record(state, pda_account, payer, system_program)
+}
+
+/// Loads the record of ER node with given identity, checking that the account is its
+/// record PDA, and that the record exists and is owned by the program
+pub fn load_record(pda_account: &AccountInfo, identity: &Pubkey) -> Result<ErRecord, ProgramError> {
+    let seeds = [ER_RECORD_SEED, identity.as_ref()];
+    let (pda, _) = Pubkey::find_program_address(&seeds, &ID);
+    let record: ErRecord = load_pda(pda_account, &pda)?;
+    if record.identity() != identity {
+        msg!("record {} belongs to another ER node", pda_account.key);
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(record)
+}
@@ -0,0 +1,159 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::{consts::ER_ATTESTATION_SEED, ID};
+
+use super::metrics::MAX_BPS;
+
+/// Number of slots aggregated in a single bucket of rolling counters, roughly an hour
+pub const ATTESTATION_BUCKET_SLOTS: u64 = 9_000;
+
+/// Number of buckets in the rolling window, i.e. observations of roughly the last day are kept
+pub const ATTESTATION_BUCKETS: usize = 24;
+
+/// Observation of ER node made by a client or watcher
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Observation {
+    /// Identity of the observed ER node
+    pub identity: Pubkey,
+    /// Base chain slot, at which the observation was made
+    pub slot: u64,
+    /// Observed round trip latency of the request to ER node in ms
+    pub latency_ms: u32,
+    /// Whether ER node served the request successfully
+    pub success: bool,
+}
+
+/// Counters of observations made within a single bucket of slots
+#[derive(Debug, Default, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct AttestationBucket {
+    /// Index of the bucket, i.e. the slot of observation divided by [ATTESTATION_BUCKET_SLOTS]
+    pub index: u64,
+    /// Number of successful requests
+    pub successes: u32,
+    /// Number of failed requests
+    pub failures: u32,
+    /// Sum of latencies of successful requests in ms
+    pub latency_sum_ms: u64,
+}
+
+/// Rolling counters of observations attested for ER node by its clients
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct AttestationRecord {
+    /// Identity of the observed ER node
+    pub identity: Pubkey,
+    /// Ring buffer of buckets, indexed by bucket index modulo [ATTESTATION_BUCKETS]
+    pub buckets: [AttestationBucket; ATTESTATION_BUCKETS],
+}
+
+/// Observations of ER node aggregated over the rolling window
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AttestationStats {
+    pub successes: u64,
+    pub failures: u64,
+    pub latency_sum_ms: u64,
+}
+
+impl AttestationRecord {
+    /// Creates empty attestation record for the given ER node
+    pub fn new(identity: Pubkey) -> Self {
+        Self {
+            identity,
+            buckets: Default::default(),
+        }
+    }
+
+    /// Computes attestation record's PDA for the given ER node identity
+    pub fn pda(identity: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ER_ATTESTATION_SEED, identity.as_ref()], &ID)
+    }
+
+    /// Adds observation to the counters, returns false if observation is
+    /// too old, i.e. its bucket was already reused for newer observations
+    pub fn record(&mut self, observation: &Observation) -> bool {
+        let index = observation.slot / ATTESTATION_BUCKET_SLOTS;
+        let bucket = &mut self.buckets[index as usize % ATTESTATION_BUCKETS];
+        if bucket.index > index {
+            return false;
+        }
+        if bucket.index < index {
+            *bucket = AttestationBucket {
+                index,
+                ..Default::default()
+            };
+        }
+        if observation.success {
+            bucket.successes = bucket.successes.saturating_add(1);
+            bucket.latency_sum_ms = bucket
+                .latency_sum_ms
+                .saturating_add(observation.latency_ms as u64);
+        } else {
+            bucket.failures = bucket.failures.saturating_add(1);
+        }
+        true
+    }
+
+    /// Aggregates observations made within the rolling window, ending at given slot
+    pub fn stats(&self, slot: u64) -> AttestationStats {
+        let current = slot / ATTESTATION_BUCKET_SLOTS;
+        self.buckets
+            .iter()
+            .filter(|b| b.index <= current && b.index + ATTESTATION_BUCKETS as u64 > current)
+            .fold(AttestationStats::default(), |mut stats, b| {
+                stats.successes += b.successes as u64;
+                stats.failures += b.failures as u64;
+                stats.latency_sum_ms += b.latency_sum_ms;
+                stats
+            })
+    }
+}
+
+impl AttestationStats {
+    /// Returns the share of successful requests in basis points, `None` if nothing was observed
+    pub fn success_rate_bps(&self) -> Option<u16> {
+        let total = self.successes + self.failures;
+        (total != 0).then(|| (self.successes * MAX_BPS as u64 / total) as u16)
+    }
+
+    /// Returns average latency of successful requests in ms, `None` if there were none
+    pub fn avg_latency_ms(&self) -> Option<u64> {
+        (self.successes != 0).then(|| self.latency_sum_ms / self.successes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation(slot: u64, latency_ms: u32, success: bool) -> Observation {
+        Observation {
+            identity: Pubkey::default(),
+            slot,
+            latency_ms,
+            success,
+        }
+    }
+
+    #[test]
+    fn test_rolling_counters() {
+        let mut record = AttestationRecord::new(Pubkey::default());
+        assert!(record.record(&observation(100, 30, true)));
+        assert!(record.record(&observation(ATTESTATION_BUCKET_SLOTS, 50, true)));
+        assert!(record.record(&observation(ATTESTATION_BUCKET_SLOTS + 1, 0, false)));
+
+        let stats = record.stats(ATTESTATION_BUCKET_SLOTS);
+        assert_eq!(stats.successes, 2);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.success_rate_bps(), Some(6_666));
+        assert_eq!(stats.avg_latency_ms(), Some(40));
+
+        // first bucket falls out of the window, and is reused by the new observation
+        let slot = ATTESTATION_BUCKET_SLOTS * ATTESTATION_BUCKETS as u64;
+        assert_eq!(record.stats(slot).successes, 1);
+        assert!(record.record(&observation(slot, 10, true)));
+        assert_eq!(record.stats(slot).avg_latency_ms(), Some(30));
+        // observations for the reused bucket's previous period are no longer accepted
+        assert!(!record.record(&observation(100, 30, true)));
+        assert_eq!(AttestationStats::default().success_rate_bps(), None);
+    }
+}
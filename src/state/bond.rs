@@ -0,0 +1,30 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::{consts::ER_BOND_SEED, ID};
+
+/// Lamports posted by ER node as collateral against misbehaviour, kept in the
+/// vault (PDA) on top of its rent exempt balance, and slashed on upheld disputes
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct BondRecord {
+    /// Identity of ER node
+    pub identity: Pubkey,
+    /// Bonded lamports
+    pub amount: u64,
+    /// Part of bonded lamports at stake in open disputes
+    pub locked: u64,
+    /// Number of open disputes against ER node
+    pub open_disputes: u32,
+}
+
+impl BondRecord {
+    /// Computes bond vault's PDA for the given ER node identity
+    pub fn pda(identity: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ER_BOND_SEED, identity.as_ref()], &ID)
+    }
+
+    /// Returns bonded lamports, which are not at stake in any dispute
+    pub fn available(&self) -> u64 {
+        self.amount.saturating_sub(self.locked)
+    }
+}
@@ -0,0 +1,35 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{hash::Hash, pubkey::Pubkey};
+
+use crate::{consts::CONFIG_SEED, ID};
+
+/// Program wide configuration of the domain registry, managed by admin, which has to
+/// stay its first field, so that config of any earlier layout can be replaced by it
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// Key allowed to update the configuration
+    pub admin: Pubkey,
+    /// Genesis hash of the base chain cluster (e.g. devnet or mainnet),
+    /// which the registry is deployed to, ER records should match it
+    pub cluster: Hash,
+    /// Number of slots, which have to pass before announced fee increase takes effect
+    pub fee_notice_slots: u64,
+    /// Key allowed to resolve disputes along with the admin
+    pub arbiter: Pubkey,
+    /// Keys allowed to attest observations of ER nodes, i.e. clients and watchers vetted by
+    /// the admin, so that rolling counters cannot be flooded by throwaway keys, at most
+    /// [MAX_ATTESTERS](crate::consts::MAX_ATTESTERS) of them
+    pub attesters: Vec<Pubkey>,
+    /// Account receiving slashed stakes, when ruling says so
+    pub treasury: Pubkey,
+    /// Number of seconds between the start of ER node's exit and its completion,
+    /// giving clients time to dispute misbehaviour before the bond is released
+    pub unbonding_cooldown_secs: i64,
+}
+
+impl Config {
+    /// Computes the PDA of program configuration
+    pub fn pda() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[CONFIG_SEED], &ID)
+    }
+}
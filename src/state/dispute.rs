@@ -0,0 +1,143 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{hash::Hash, pubkey::Pubkey};
+
+use crate::{consts::ER_DISPUTE_SEED, error::MdpError, ID};
+
+/// Dispute opened by challenger against bonded ER node, the challenger posts counter bond,
+/// matched by the same amount of ER node's bond, which is locked until the resolution,
+/// the losing side's stake is slashed to the winner or to the treasury
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct DisputeRecord {
+    /// Identity of the disputed ER node
+    pub identity: Pubkey,
+    /// Key which opened the dispute and posted counter bond
+    pub challenger: Pubkey,
+    /// Kind of alleged misbehaviour
+    pub reason: DisputeReason,
+    /// Hash of the evidence, kept off chain, e.g. transcript of failed requests
+    pub evidence: Hash,
+    /// Lamports at stake on each side
+    pub stake: u64,
+    /// Slot, at which the dispute was opened
+    pub opened_slot: u64,
+    /// Current state of the dispute
+    pub state: DisputeState,
+}
+
+/// Alleged misbehaviour of ER node
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+#[borsh(use_discriminant = true)]
+pub enum DisputeReason {
+    /// advertised endpoint doesn't serve ER node
+    WrongEndpoint = 0,
+    /// advertised status doesn't match observed one
+    FalseStatus = 1,
+    /// anything else
+    Other = 2,
+}
+
+/// States of the dispute, `Open` is the only one, which can be transitioned from
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeState {
+    /// Awaiting resolution by admin or arbiter
+    Open,
+    /// Misbehaviour was proven, ER node's stake is slashed
+    Upheld(Resolution),
+    /// Misbehaviour wasn't proven, challenger's counter bond is slashed
+    Rejected(Resolution),
+}
+
+/// Outcome of the dispute resolution
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Resolution {
+    /// Slot, at which the dispute was resolved
+    pub slot: u64,
+    /// Account, which received the slashed stake
+    pub slashed_to: Pubkey,
+}
+
+/// Arguments of dispute opening
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct DisputeClaim {
+    /// Identity of the disputed ER node
+    pub identity: Pubkey,
+    pub reason: DisputeReason,
+    pub evidence: Hash,
+    /// Counter bond in lamports, posted by challenger
+    pub stake: u64,
+}
+
+/// Decision made by admin or arbiter
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Ruling {
+    /// Whether the misbehaviour was proven
+    pub upheld: bool,
+    /// Where the slashed stake goes
+    pub slash_to: SlashRecipient,
+}
+
+/// Recipient of the slashed stake
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+#[borsh(use_discriminant = true)]
+pub enum SlashRecipient {
+    /// the winning side, i.e. challenger, or ER node's bond
+    Winner = 0,
+    /// treasury set in program config
+    Treasury = 1,
+}
+
+impl DisputeRecord {
+    /// Computes dispute's PDA for the given ER node and challenger, i.e. challenger
+    /// can have at most one open dispute against the same ER node, as the dispute
+    /// is closed once resolved
+    pub fn pda(identity: &Pubkey, challenger: &Pubkey) -> (Pubkey, u8) {
+        let seeds = [ER_DISPUTE_SEED, identity.as_ref(), challenger.as_ref()];
+        Pubkey::find_program_address(&seeds, &ID)
+    }
+
+    /// Transitions open dispute to the state decided by the ruling
+    pub fn resolve(&mut self, ruling: &Ruling, resolution: Resolution) -> Result<(), MdpError> {
+        if self.state != DisputeState::Open {
+            return Err(MdpError::DisputeNotOpen);
+        }
+        self.state = if ruling.upheld {
+            DisputeState::Upheld(resolution)
+        } else {
+            DisputeState::Rejected(resolution)
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dispute_transitions() {
+        let mut dispute = DisputeRecord {
+            identity: Pubkey::new_unique(),
+            challenger: Pubkey::new_unique(),
+            reason: DisputeReason::WrongEndpoint,
+            evidence: Hash::default(),
+            stake: 1_000,
+            opened_slot: 10,
+            state: DisputeState::Open,
+        };
+        let ruling = Ruling {
+            upheld: true,
+            slash_to: SlashRecipient::Winner,
+        };
+        let resolution = Resolution {
+            slot: 20,
+            slashed_to: dispute.challenger,
+        };
+        assert_eq!(dispute.resolve(&ruling, resolution), Ok(()));
+        assert_eq!(dispute.state, DisputeState::Upheld(resolution));
+        // resolved dispute is final
+        assert_eq!(
+            dispute.resolve(&ruling, resolution),
+            Err(MdpError::DisputeNotOpen)
+        );
+    }
+}
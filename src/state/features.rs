@@ -11,11 +11,23 @@ const FEATURESET_BYTES: usize = 32;
 pub struct FeaturesSet([u8; FEATURESET_BYTES]);
 
 /// Individual custom extra feature supported by validator
-#[derive(Clone, Copy)]
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+#[borsh(use_discriminant = true)]
 #[repr(u8)]
 pub enum Feature {
     Randomness = 0,
     HighResClock = 1,
+    /// node runs inside of TEE, and publishes its hardware attestation
+    TeeAttestation = 2,
+}
+
+impl Feature {
+    /// All of the known features, ordered by their id
+    pub const ALL: [Feature; 3] = [
+        Feature::Randomness,
+        Feature::HighResClock,
+        Feature::TeeAttestation,
+    ];
 }
 
 impl FeaturesSet {
@@ -44,9 +56,15 @@ impl FeaturesSet {
     }
 
     /// Returns true if given featureset has requested feature enabled
-    pub fn contains(&mut self, feature: Feature) -> bool {
-        let (segment, offset) = self.locate(feature);
-        (*segment & (1 << offset)) >> offset == 1
+    pub fn contains(&self, feature: Feature) -> bool {
+        let index = feature as usize / Self::SEGMENT;
+        let offset = feature as usize % Self::SEGMENT;
+        (self.0[index] & (1 << offset)) >> offset == 1
+    }
+
+    /// Returns true if all features enabled in other featureset are enabled in this one
+    pub fn includes(&self, other: &FeaturesSet) -> bool {
+        self.0.iter().zip(other.0).all(|(s, o)| s & o == o)
     }
 }
 
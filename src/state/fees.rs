@@ -0,0 +1,187 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::{
+    features::{Feature, FeaturesSet},
+    metrics::MAX_BPS,
+};
+
+/// Number of micro-lamports in one lamport, compute unit price is quoted in micro-lamports
+pub const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
+
+/// Pricing of transactions executed by ER node
+#[derive(Debug, Default, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct FeeSchedule {
+    /// Lamports charged per transaction signature
+    pub lamports_per_signature: u64,
+    /// Micro-lamports charged per consumed compute unit
+    pub micro_lamports_per_cu: u64,
+    /// Extra lamports charged per transaction, making use of specific features
+    pub surcharges: Vec<FeatureSurcharge>,
+    /// Multiplier in basis points applied to the whole fee of priority
+    /// transactions, `None` if ER node doesn't offer priority tier
+    pub priority_multiplier_bps: Option<u16>,
+}
+
+/// Extra fee charged for transaction using particular feature
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct FeatureSurcharge {
+    pub feature: Feature,
+    /// Lamports charged on top of base fee
+    pub lamports: u64,
+}
+
+/// Fee schedule announced by ER node, which takes effect at given slot
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct PendingFees {
+    pub fees: FeeSchedule,
+    /// Slot starting from which the pending schedule replaces the current one
+    pub effective_slot: u64,
+}
+
+/// Shape of transaction, used to estimate its cost against ER node
+#[derive(Debug, Default, Clone)]
+pub struct TxProfile {
+    /// Number of signatures in transaction
+    pub signatures: u32,
+    /// Number of compute units transaction is expected to consume
+    pub compute_units: u32,
+    /// Features transaction makes use of
+    pub features: FeaturesSet,
+    /// Whether transaction should be executed in priority tier
+    pub priority: bool,
+}
+
+impl FeeSchedule {
+    /// Returns true if there's at most one surcharge per feature
+    /// and priority multiplier doesn't make transactions cheaper
+    pub fn is_valid(&self) -> bool {
+        let unique = self
+            .surcharges
+            .iter()
+            .enumerate()
+            .all(|(i, s)| self.surcharges[..i].iter().all(|o| o.feature != s.feature));
+        unique && self.priority_multiplier_bps.is_none_or(|m| m >= MAX_BPS)
+    }
+
+    /// Returns true if any of the fee components is higher in this schedule
+    /// than in the current one, which makes some transaction more expensive,
+    /// introduction of priority tier is considered to be an increase as well
+    pub fn raises(&self, current: &FeeSchedule) -> bool {
+        let surcharge = |feature| {
+            current
+                .surcharges
+                .iter()
+                .find(|s| s.feature == feature)
+                .map_or(0, |s| s.lamports)
+        };
+        self.lamports_per_signature > current.lamports_per_signature
+            || self.micro_lamports_per_cu > current.micro_lamports_per_cu
+            || self.priority_multiplier_bps > current.priority_multiplier_bps
+            || self
+                .surcharges
+                .iter()
+                .any(|s| s.lamports > surcharge(s.feature))
+    }
+
+    /// Estimates the cost of transaction with given profile in lamports, the
+    /// compute part is rounded up, and priority multiplier is only applied if
+    /// requested and offered, `None` is returned if the estimate overflows
+    pub fn estimate(&self, profile: &TxProfile) -> Option<u64> {
+        let signatures = self
+            .lamports_per_signature
+            .checked_mul(profile.signatures as u64)?;
+        let compute = self
+            .micro_lamports_per_cu
+            .checked_mul(profile.compute_units as u64)?
+            .div_ceil(MICRO_LAMPORTS_PER_LAMPORT);
+        let mut fee = signatures.checked_add(compute)?;
+        for s in &self.surcharges {
+            if profile.features.contains(s.feature) {
+                fee = fee.checked_add(s.lamports)?;
+            }
+        }
+        match self.priority_multiplier_bps {
+            Some(multiplier) if profile.priority => {
+                let fee = fee as u128 * multiplier as u128 / MAX_BPS as u128;
+                u64::try_from(fee).ok()
+            }
+            _ => Some(fee),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_estimate() {
+        let mut schedule = FeeSchedule {
+            lamports_per_signature: 5_000,
+            micro_lamports_per_cu: 1_500,
+            surcharges: vec![FeatureSurcharge {
+                feature: Feature::Randomness,
+                lamports: 10_000,
+            }],
+            priority_multiplier_bps: Some(15_000),
+        };
+        let mut profile = TxProfile {
+            signatures: 2,
+            compute_units: 200_001,
+            ..Default::default()
+        };
+        // 2 * 5000 + ceil(200_001 * 1500 / 10^6)
+        assert_eq!(schedule.estimate(&profile), Some(10_301));
+        profile.features = FeaturesSet::default().activate(Feature::Randomness);
+        assert_eq!(schedule.estimate(&profile), Some(20_301));
+        profile.priority = true;
+        assert_eq!(schedule.estimate(&profile), Some(30_451));
+        schedule.priority_multiplier_bps = None;
+        assert_eq!(schedule.estimate(&profile), Some(20_301));
+    }
+
+    #[test]
+    fn test_fee_raises() {
+        let current = FeeSchedule {
+            lamports_per_signature: 5_000,
+            micro_lamports_per_cu: 1_000,
+            surcharges: vec![],
+            priority_multiplier_bps: None,
+        };
+        let mut fees = current.clone();
+        assert!(!fees.raises(&current));
+        fees.lamports_per_signature = 4_000;
+        assert!(!fees.raises(&current));
+        fees.micro_lamports_per_cu = 1_001;
+        assert!(fees.raises(&current));
+        fees = current.clone();
+        fees.surcharges.push(FeatureSurcharge {
+            feature: Feature::HighResClock,
+            lamports: 1,
+        });
+        assert!(fees.raises(&current));
+        assert!(!current.raises(&fees));
+        fees.surcharges.clear();
+        fees.priority_multiplier_bps = Some(MAX_BPS);
+        assert!(fees.raises(&current));
+    }
+
+    #[test]
+    fn test_fee_schedule_validity() {
+        let surcharge = FeatureSurcharge {
+            feature: Feature::Randomness,
+            lamports: 1,
+        };
+        let mut schedule = FeeSchedule {
+            surcharges: vec![surcharge],
+            priority_multiplier_bps: Some(MAX_BPS),
+            ..Default::default()
+        };
+        assert!(schedule.is_valid());
+        schedule.priority_multiplier_bps = Some(MAX_BPS - 1);
+        assert!(!schedule.is_valid());
+        schedule.priority_multiplier_bps = None;
+        schedule.surcharges.push(surcharge);
+        assert!(!schedule.is_valid());
+    }
+}
@@ -0,0 +1,102 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Number of fixed-point units in a single degree of latitude or longitude
+pub const MICRODEGREES: i32 = 1_000_000;
+
+/// Mean radius of the Earth in kilometers
+const EARTH_RADIUS_KM: f64 = 6_371.0;
+
+/// Coarse geographic region of ER node, finer than a continent,
+/// roughly following the regions of major cloud providers
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[borsh(use_discriminant = true)]
+pub enum Region {
+    NorthAmericaEast = 0,
+    NorthAmericaCentral = 1,
+    NorthAmericaWest = 2,
+    SouthAmerica = 3,
+    EuropeWest = 4,
+    EuropeCentral = 5,
+    EuropeNorth = 6,
+    MiddleEast = 7,
+    Africa = 8,
+    AsiaSouth = 9,
+    AsiaSoutheast = 10,
+    AsiaEast = 11,
+    Oceania = 12,
+}
+
+/// Approximate location of ER node, in fixed-point millionths of a degree
+#[derive(Debug, Default, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct GeoPoint {
+    /// Latitude, positive to the north of the equator
+    pub latitude: i32,
+    /// Longitude, positive to the east of the prime meridian
+    pub longitude: i32,
+}
+
+impl GeoPoint {
+    /// Creates point out of latitude and longitude given in degrees
+    pub fn from_degrees(latitude: f64, longitude: f64) -> Self {
+        let fixed = |degrees: f64| (degrees * MICRODEGREES as f64).round() as i32;
+        Self {
+            latitude: fixed(latitude),
+            longitude: fixed(longitude),
+        }
+    }
+
+    /// Returns latitude and longitude in degrees
+    pub fn to_degrees(&self) -> (f64, f64) {
+        let degrees = |fixed: i32| fixed as f64 / MICRODEGREES as f64;
+        (degrees(self.latitude), degrees(self.longitude))
+    }
+
+    /// Returns true if coordinates are within their valid ranges
+    pub fn is_valid(&self) -> bool {
+        // unsigned_abs, as abs overflows on i32::MIN
+        self.latitude.unsigned_abs() <= 90 * MICRODEGREES as u32
+            && self.longitude.unsigned_abs() <= 180 * MICRODEGREES as u32
+    }
+
+    /// Computes great-circle distance to another point in kilometers, using haversine formula
+    pub fn distance_km(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lon1) = self.to_degrees();
+        let (lat2, lon2) = other.to_degrees();
+        let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (lon2 - lon1).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        let new_york = GeoPoint::from_degrees(40.7128, -74.0060);
+        let los_angeles = GeoPoint::from_degrees(34.0522, -118.2437);
+        let london = GeoPoint::from_degrees(51.5074, -0.1278);
+        assert!((new_york.distance_km(&los_angeles) - 3_936.0).abs() < 10.0);
+        assert!((new_york.distance_km(&london) - 5_570.0).abs() < 10.0);
+        assert_eq!(london.distance_km(&london), 0.0);
+        // across the antimeridian
+        let fiji = GeoPoint::from_degrees(-17.7, 178.0);
+        let samoa = GeoPoint::from_degrees(-13.8, -172.0);
+        assert!(fiji.distance_km(&samoa) < 1_200.0);
+    }
+
+    #[test]
+    fn test_validity() {
+        assert!(GeoPoint::from_degrees(-90.0, 180.0).is_valid());
+        assert!(!GeoPoint::from_degrees(90.5, 0.0).is_valid());
+        assert!(!GeoPoint::from_degrees(0.0, -181.0).is_valid());
+        let min = GeoPoint {
+            latitude: i32::MIN,
+            longitude: i32::MIN,
+        };
+        assert!(!min.is_valid());
+    }
+}
@@ -0,0 +1,147 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::{consts::ER_INDEX_SEED, ID};
+
+use super::{
+    features::Feature,
+    record::{CountryCode, ErRecord},
+    status::ErStatus,
+};
+
+/// Number of pages each index is sharded into, the page holding given identity is
+/// derived from the identity itself, so that it can be located without a scan
+pub const INDEX_PAGES: u8 = 16;
+
+/// Attribute, which ER records are indexed by
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum IndexKey {
+    Country(CountryCode),
+    Feature(Feature),
+}
+
+/// Single page of secondary index, listing identities of ER nodes matching the key
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct IndexPage {
+    pub key: IndexKey,
+    pub page: u8,
+    pub identities: Vec<Pubkey>,
+}
+
+impl IndexKey {
+    /// Returns keys the given record should be listed under, ordered by country first,
+    /// then by feature id, deregistering records aren't listed in any of the indexes
+    pub fn of(record: &ErRecord) -> Vec<IndexKey> {
+        if record.status() == ErStatus::Deregistering {
+            return Vec::new();
+        }
+        let features = Feature::ALL
+            .into_iter()
+            .filter(|&f| record.features().contains(f))
+            .map(IndexKey::Feature);
+        std::iter::once(IndexKey::Country(record.country_code()))
+            .chain(features)
+            .collect()
+    }
+
+    /// Returns byte representation of the key used in PDA derivation
+    pub fn seed(&self) -> [u8; 4] {
+        match self {
+            Self::Country(code) => {
+                let [a, b, c] = *code.as_bytes();
+                [0, a, b, c]
+            }
+            Self::Feature(feature) => [1, *feature as u8, 0, 0],
+        }
+    }
+
+    /// Returns index page PDAs of the key, to be fetched in order to list all matching records
+    pub fn pages(&self) -> impl Iterator<Item = Pubkey> + '_ {
+        (0..INDEX_PAGES).map(|page| IndexPage::pda(self, page).0)
+    }
+}
+
+impl IndexPage {
+    /// Creates empty page of the index
+    pub fn new(key: IndexKey, page: u8) -> Self {
+        Self {
+            key,
+            page,
+            identities: Vec::new(),
+        }
+    }
+
+    /// Returns the number of page, which lists the given identity
+    pub fn of(identity: &Pubkey) -> u8 {
+        identity.as_ref()[0] % INDEX_PAGES
+    }
+
+    /// Computes PDA of index page with given number
+    pub fn pda(key: &IndexKey, page: u8) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ER_INDEX_SEED, &key.seed(), &[page]], &ID)
+    }
+
+    /// Adds identity to the page, returns false if it's already listed
+    pub fn insert(&mut self, identity: Pubkey) -> bool {
+        if self.identities.contains(&identity) {
+            return false;
+        }
+        self.identities.push(identity);
+        true
+    }
+
+    /// Removes identity from the page, returns false if it isn't listed
+    pub fn remove(&mut self, identity: &Pubkey) -> bool {
+        let Some(position) = self.identities.iter().position(|i| i == identity) else {
+            return false;
+        };
+        self.identities.swap_remove(position);
+        true
+    }
+}
+
+/// Returns index page PDAs, which list the given record, these have to be passed to
+/// the instructions adding the record to indexes (Register) or removing it from all
+/// of them (BeginUnregister)
+pub fn listed_pages(record: &ErRecord) -> Vec<Pubkey> {
+    let page = IndexPage::of(record.identity());
+    IndexKey::of(record)
+        .iter()
+        .map(|k| IndexPage::pda(k, page).0)
+        .collect()
+}
+
+/// Returns index page PDAs, which have to be passed (in any order) to the instruction
+/// changing indexed fields of ER node's record from `before` to `after`
+pub fn touched_pages(before: &ErRecord, after: &ErRecord) -> Vec<Pubkey> {
+    let old = IndexKey::of(before);
+    let new = IndexKey::of(after);
+    let page = IndexPage::of(after.identity());
+    old.iter()
+        .filter(|k| !new.contains(k))
+        .chain(new.iter().filter(|k| !old.contains(k)))
+        .map(|k| IndexPage::pda(k, page).0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_insert_remove() {
+        let key = IndexKey::Feature(Feature::Randomness);
+        let mut page = IndexPage::new(key, 0);
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(page.insert(a));
+        assert!(page.insert(b));
+        assert!(!page.insert(a));
+        assert!(page.remove(&a));
+        assert!(!page.remove(&a));
+        assert_eq!(page.identities, vec![b]);
+        assert_ne!(
+            IndexPage::pda(&key, 0),
+            IndexPage::pda(&IndexKey::Country(CountryCode::from("001")), 0)
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::status::ErStatus;
+
+/// How long (in seconds) before the start of maintenance window,
+/// ER node is considered to be draining, i.e. not accepting new delegations
+pub const MAINTENANCE_DRAIN_LEAD_SECS: i64 = 15 * 60;
+
+/// Announced period of time, during which ER node is going to be unavailable
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    /// Unix timestamp (seconds) when maintenance starts
+    pub start: i64,
+    /// Unix timestamp (seconds) when maintenance ends, exclusive
+    pub end: i64,
+    /// Reason for taking the node offline
+    pub reason: MaintenanceReason,
+}
+
+/// Reason code for scheduled maintenance
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+#[borsh(use_discriminant = true)]
+pub enum MaintenanceReason {
+    /// validator software upgrade
+    Upgrade = 0,
+    /// hardware replacement or repair
+    Hardware = 1,
+    /// network reconfiguration
+    Network = 2,
+    /// anything else
+    Other = 3,
+}
+
+impl MaintenanceWindow {
+    /// Returns true if window has positive duration
+    pub fn is_valid(&self) -> bool {
+        self.start < self.end
+    }
+
+    /// Returns the status imposed by the window on ER node at given unix timestamp,
+    /// `Offline` during the window, `Draining` shortly before it and `None` otherwise
+    pub fn status_at(&self, now: i64) -> Option<ErStatus> {
+        if self.start <= now && now < self.end {
+            Some(ErStatus::Offline)
+        } else if self.start.saturating_sub(MAINTENANCE_DRAIN_LEAD_SECS) <= now && now < self.start
+        {
+            Some(ErStatus::Draining)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_status() {
+        let window = MaintenanceWindow {
+            start: 10_000,
+            end: 20_000,
+            reason: MaintenanceReason::Upgrade,
+        };
+        assert_eq!(window.status_at(0), None);
+        assert_eq!(
+            window.status_at(10_000 - MAINTENANCE_DRAIN_LEAD_SECS),
+            Some(ErStatus::Draining)
+        );
+        assert_eq!(window.status_at(10_000), Some(ErStatus::Offline));
+        assert_eq!(window.status_at(19_999), Some(ErStatus::Offline));
+        assert_eq!(window.status_at(20_000), None);
+    }
+}
@@ -0,0 +1,81 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Basis points denominator, i.e. 100%
+pub const MAX_BPS: u16 = 10_000;
+
+/// Structured load metrics, periodically reported by ER node
+#[derive(Debug, Default, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct LoadMetrics {
+    /// Current throughput in transactions per second
+    pub tps: u32,
+    /// Median transaction latency in ms
+    pub latency_p50_ms: u16,
+    /// 99th percentile of transaction latency in ms
+    pub latency_p99_ms: u16,
+    /// Number of accounts currently delegated to ER node
+    pub active_delegations: u32,
+    /// Maximum number of delegated accounts ER node is willing to serve,
+    /// zero means that the node doesn't advertise any limit
+    pub max_delegations: u32,
+    /// Memory pressure in basis points, i.e. 0 is idle and 10000 is exhausted
+    pub memory_pressure_bps: u16,
+}
+
+impl LoadMetrics {
+    /// Returns normalized utilization of ER node in basis points (0 - 10000), i.e. the
+    /// highest of delegated accounts occupancy and memory pressure, the value is
+    /// comparable across nodes, so selection logic can prefer less utilized ones
+    pub fn utilization(&self) -> u16 {
+        let occupancy = match self.max_delegations {
+            0 => 0,
+            max => (self.active_delegations as u64 * MAX_BPS as u64 / max as u64)
+                .min(MAX_BPS as u64) as u16,
+        };
+        occupancy.max(self.memory_pressure_bps.min(MAX_BPS))
+    }
+
+    /// Returns how many more accounts can be delegated to ER node,
+    /// or `None` if the node doesn't advertise any limit
+    pub fn remaining_capacity(&self) -> Option<u32> {
+        match self.max_delegations {
+            0 => None,
+            max => Some(max.saturating_sub(self.active_delegations)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utilization() {
+        let mut metrics = LoadMetrics {
+            active_delegations: 250,
+            max_delegations: 1000,
+            memory_pressure_bps: 1_000,
+            ..Default::default()
+        };
+        assert_eq!(metrics.utilization(), 2_500);
+        metrics.memory_pressure_bps = 6_000;
+        assert_eq!(metrics.utilization(), 6_000);
+        metrics.active_delegations = 2_000;
+        assert_eq!(metrics.utilization(), MAX_BPS);
+        metrics.max_delegations = 0;
+        assert_eq!(metrics.utilization(), 6_000);
+    }
+
+    #[test]
+    fn test_remaining_capacity() {
+        let mut metrics = LoadMetrics {
+            active_delegations: 250,
+            max_delegations: 1000,
+            ..Default::default()
+        };
+        assert_eq!(metrics.remaining_capacity(), Some(750));
+        metrics.active_delegations = 1_200;
+        assert_eq!(metrics.remaining_capacity(), Some(0));
+        metrics.max_delegations = 0;
+        assert_eq!(metrics.remaining_capacity(), None);
+    }
+}
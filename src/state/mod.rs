@@ -1,4 +1,18 @@
+pub mod attestation;
+pub mod bond;
+pub mod config;
+pub mod dispute;
 pub mod features;
+pub mod fees;
+pub mod geo;
+pub mod index;
+pub mod maintenance;
+pub mod metrics;
+pub mod name;
 pub mod record;
+pub mod reputation;
+pub mod semver;
 pub mod status;
+pub mod tee;
+pub mod tls;
 pub mod version;
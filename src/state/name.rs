@@ -0,0 +1,179 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{hash::hash, pubkey::Pubkey};
+
+use crate::{
+    consts::{ER_NAME_SEED, ER_RECORD_SEED, ER_REVERSE_NAME_SEED, MAX_NAME_LEN},
+    ID,
+};
+
+/// Maximum length of a single dot separated label in the name
+const MAX_LABEL_LEN: usize = 63;
+
+/// Human readable name (e.g. `eu-west-1.magicblock`), uniquely mapped to ER node identity,
+/// names form a hierarchy, where the owner of the parent (`magicblock`) authorizes the
+/// creation of its children (`eu-west-1.magicblock`)
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct NameRecord {
+    /// Owner of the name, allowed to release or transfer it, and to create its children
+    pub owner: Pubkey,
+    /// Identity of ER node, which the name resolves to, if any,
+    /// names used purely as namespaces might not resolve to anything
+    pub identity: Option<Pubkey>,
+    /// Address of the parent name record, `None` for top level names
+    pub parent: Option<Pubkey>,
+    /// Number of existing child names, the name cannot be released while it has any
+    pub children: u32,
+    /// Normalized name
+    pub name: String,
+}
+
+/// Reverse mapping from ER node identity to its name
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct ReverseNameRecord {
+    /// Normalized name, which resolves to the ER node
+    pub name: String,
+}
+
+impl NameRecord {
+    /// Computes name record's PDA for the given normalized name
+    pub fn pda(name: &str) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ER_NAME_SEED, &name_hash(name)], &ID)
+    }
+}
+
+impl ReverseNameRecord {
+    /// Computes reverse name record's PDA for the given ER node identity
+    pub fn pda(identity: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ER_REVERSE_NAME_SEED, identity.as_ref()], &ID)
+    }
+}
+
+/// Returns the parent of the normalized name, i.e. the name without its first label
+pub fn parent_name(name: &str) -> Option<&str> {
+    name.split_once('.').map(|(_, parent)| parent)
+}
+
+/// Resolves arbitrary user provided name to ER node identity, walking up the hierarchy
+/// to make sure, that the name and each of its ancestors are properly linked together,
+/// `fetch` should return name record stored at the given address, if it exists.
+///
+/// Names are owned independently of ER nodes, so they are kept when ER node exits the
+/// registry, `registered` should tell whether ER record exists at the given address,
+/// and the names of ER nodes, which are no longer registered, don't resolve.
+pub fn resolve(
+    name: &str,
+    mut fetch: impl FnMut(&Pubkey) -> Option<NameRecord>,
+    registered: impl FnOnce(&Pubkey) -> bool,
+) -> Option<Pubkey> {
+    let mut current = normalize(name)?;
+    let record = fetch(&NameRecord::pda(&current).0).filter(|r| r.name == current)?;
+    let mut link = record.parent;
+    while let Some(parent) = parent_name(&current) {
+        let address = NameRecord::pda(parent).0;
+        if link != Some(address) {
+            return None;
+        }
+        let ancestor = fetch(&address).filter(|r| r.name == parent)?;
+        link = ancestor.parent;
+        current = ancestor.name;
+    }
+    // top level name cannot have a parent
+    let identity = link.is_none().then_some(record.identity).flatten()?;
+    let (pda, _) = Pubkey::find_program_address(&[ER_RECORD_SEED, identity.as_ref()], &ID);
+    registered(&pda).then_some(identity)
+}
+
+/// Forward lookup: returns the address of name record (holding ER node identity) for
+/// arbitrary user provided name, or `None` if the name cannot be normalized
+pub fn forward_lookup(name: &str) -> Option<Pubkey> {
+    normalize(name).map(|name| NameRecord::pda(&name).0)
+}
+
+/// Reverse lookup: returns the address of reverse name record (holding the name) for ER node identity
+pub fn reverse_lookup(identity: &Pubkey) -> Pubkey {
+    ReverseNameRecord::pda(identity).0
+}
+
+/// Returns hash of the normalized name, used as PDA seed, since names can be longer than seeds
+pub fn name_hash(name: &str) -> [u8; 32] {
+    hash(name.as_bytes()).to_bytes()
+}
+
+/// Normalizes the name to canonical form, i.e. lowercase dot separated labels, consisting of
+/// ascii alphanumerics and hyphens (not at the label edges), returns `None` if name is invalid
+pub fn normalize(name: &str) -> Option<String> {
+    let name = name.trim().to_ascii_lowercase();
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return None;
+    }
+    let valid_label = |label: &str| {
+        !label.is_empty()
+            && label.len() <= MAX_LABEL_LEN
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+    };
+    name.split('.').all(valid_label).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize(" EU-West-1.MagicBlock ").as_deref(),
+            Some("eu-west-1.magicblock")
+        );
+        assert_eq!(normalize(""), None);
+        assert_eq!(normalize("eu..magicblock"), None);
+        assert_eq!(normalize("-eu.magicblock"), None);
+        assert_eq!(normalize("eu_west.magicblock"), None);
+        assert_eq!(normalize(&"a".repeat(MAX_LABEL_LEN + 1)), None);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let identity = Pubkey::new_unique();
+        let record = |name: &str, parent: Option<&str>, identity| NameRecord {
+            owner: Pubkey::default(),
+            identity,
+            parent: parent.map(|p| NameRecord::pda(p).0),
+            children: 0,
+            name: name.to_string(),
+        };
+        let mut names = vec![
+            record("acme", None, None),
+            record("node1.acme", Some("acme"), Some(identity)),
+            record("node2.acme", Some("node1.acme"), Some(identity)),
+        ];
+        let fetch = |names: &[NameRecord], address: &Pubkey| {
+            names
+                .iter()
+                .find(|r| NameRecord::pda(&r.name).0 == *address)
+                .cloned()
+        };
+        let registered = |address: &Pubkey| {
+            *address == Pubkey::find_program_address(&[ER_RECORD_SEED, identity.as_ref()], &ID).0
+        };
+        let resolved = resolve("Node1.Acme", |a| fetch(&names, a), registered);
+        assert_eq!(resolved, Some(identity));
+        assert_eq!(resolve("acme", |a| fetch(&names, a), registered), None);
+        // ER node has exited the registry
+        assert_eq!(resolve("node1.acme", |a| fetch(&names, a), |_| false), None);
+        // child is linked to the wrong parent
+        assert_eq!(
+            resolve("node2.acme", |a| fetch(&names, a), registered),
+            None
+        );
+        // parent is missing
+        names.remove(0);
+        assert_eq!(
+            resolve("node1.acme", |a| fetch(&names, a), registered),
+            None
+        );
+    }
+}
@@ -1,13 +1,59 @@
-use super::{features::FeaturesSet, status::ErStatus, version::v0::RecordV0};
-use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::pubkey::Pubkey;
+use super::{
+    features::{Feature, FeaturesSet},
+    fees::{FeeSchedule, PendingFees, TxProfile},
+    geo::{GeoPoint, Region},
+    maintenance::MaintenanceWindow,
+    metrics::LoadMetrics,
+    reputation::Heartbeats,
+    semver::SemVer,
+    status::ErStatus,
+    tee::TeeAttestation,
+    tls::SpkiFingerprint,
+    version::{v0::RecordV0, v1::RecordV1},
+};
+use borsh::{
+    io::{self, Read, Write},
+    BorshDeserialize, BorshSerialize,
+};
+use solana_program::{hash::Hash, pubkey::Pubkey};
 
 use crate::{consts::ER_RECORD_SEED, ID};
 
-#[derive(Debug, BorshSerialize, BorshDeserialize)]
+/// Borsh tag of the legacy record layout, see [RecordV0]
+const V0_TAG: u8 = 0;
+/// Borsh tag of the current record layout, see [RecordV1]
+pub const V1_TAG: u8 = 1;
+
+/// ER domain registry record, records stored in the legacy [RecordV0] layout are
+/// upgraded on read, and written back in the current layout on their next update
+#[derive(Debug)]
 #[cfg_attr(not(feature = "entrypoint"), derive(PartialEq, Eq, Clone))]
 pub enum ErRecord {
-    V0(RecordV0),
+    V1(RecordV1),
+}
+
+impl BorshSerialize for ErRecord {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Self::V1(v) => {
+                V1_TAG.serialize(writer)?;
+                v.serialize(writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for ErRecord {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match u8::deserialize_reader(reader)? {
+            V0_TAG => RecordV0::deserialize_reader(reader).map(|v| Self::V1(v.into())),
+            V1_TAG => RecordV1::deserialize_reader(reader).map(Self::V1),
+            tag => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown record version {tag}"),
+            )),
+        }
+    }
 }
 
 impl ErRecord {
@@ -24,105 +70,402 @@ impl ErRecord {
     /// Returns identity pubkey of the given ER
     pub fn identity(&self) -> &Pubkey {
         match self {
-            Self::V0(r) => &r.identity,
+            Self::V1(r) => &r.identity,
+        }
+    }
+
+    /// Returns the key, which is allowed to batch update the given ER record
+    pub fn authority(&self) -> &Pubkey {
+        match self {
+            Self::V1(r) => &r.authority,
+        }
+    }
+
+    /// Returns genesis hash of the base chain cluster, the given ER node settles to
+    pub fn cluster(&self) -> &Hash {
+        match self {
+            Self::V1(r) => &r.cluster,
+        }
+    }
+
+    /// Returns identifier of the chain run by the given ER node
+    pub fn chain_id(&self) -> u64 {
+        match self {
+            Self::V1(r) => r.chain_id,
         }
     }
 
     /// Returns FQDN address for the given ER node
     pub fn addr(&self) -> &str {
         match self {
-            Self::V0(v) => &v.addr,
+            Self::V1(v) => &v.addr,
+        }
+    }
+
+    /// Returns fingerprint of TLS certificate's public key served at ER node's address, if pinned
+    pub fn tls_fingerprint(&self) -> Option<&SpkiFingerprint> {
+        match self {
+            Self::V1(v) => v.tls_fingerprint.as_ref(),
         }
     }
 
-    /// Returns transaction fees of given ER node
-    pub fn base_fee(&self) -> u16 {
+    /// Returns fee schedule of given ER node, as of the last sync, see
+    /// [ErRecord::effective_fees] for the one in force at particular slot
+    pub fn fees(&self) -> &FeeSchedule {
         match self {
-            Self::V0(v) => v.base_fee,
+            Self::V1(v) => &v.fees,
         }
     }
 
+    /// Returns announced fee increase of given ER node, which is not yet settled
+    pub fn pending_fees(&self) -> Option<&PendingFees> {
+        match self {
+            Self::V1(v) => v.pending_fees.as_ref(),
+        }
+    }
+
+    /// Returns fee schedule of given ER node in force at given slot
+    pub fn effective_fees(&self, slot: u64) -> &FeeSchedule {
+        match self.pending_fees() {
+            Some(pending) if pending.effective_slot <= slot => &pending.fees,
+            _ => self.fees(),
+        }
+    }
+
+    /// Estimates the cost in lamports of transaction with given profile, executed
+    /// by the given ER node at given slot, `None` if the node doesn't support some of
+    /// the features used by transaction, or the estimate overflows, see [FeeSchedule::estimate]
+    pub fn estimate_fee(&self, profile: &TxProfile, slot: u64) -> Option<u64> {
+        if !self.features().includes(&profile.features) {
+            return None;
+        }
+        self.effective_fees(slot).estimate(profile)
+    }
+
     /// Returns supported set of features by the given ER node
     pub fn features(&self) -> &FeaturesSet {
         match self {
-            Self::V0(v) => &v.features,
+            Self::V1(v) => &v.features,
         }
     }
 
     /// Returns the block time in ms of the given ER node
     pub fn block_time_ms(&self) -> u16 {
         match self {
-            Self::V0(v) => v.block_time_ms,
+            Self::V1(v) => v.block_time_ms,
         }
     }
 
     /// Returns current status of the given ER node
     pub fn status(&self) -> ErStatus {
         match self {
-            Self::V0(v) => v.status,
+            Self::V1(v) => v.status,
+        }
+    }
+
+    /// Returns the status of the given ER node at given unix timestamp, taking
+    /// announced maintenance windows into account, should be preferred over
+    /// raw status by clients, when selecting the node to work with
+    pub fn effective_status(&self, now: i64) -> ErStatus {
+        let mut status = self.status();
+        if matches!(status, ErStatus::Offline | ErStatus::Deregistering) {
+            return status;
+        }
+        for imposed in self.maintenance().iter().filter_map(|w| w.status_at(now)) {
+            if imposed == ErStatus::Offline {
+                return imposed;
+            }
+            status = imposed;
+        }
+        status
+    }
+
+    /// Returns the version of validator software run by the given ER node
+    pub fn software_version(&self) -> SemVer {
+        match self {
+            Self::V1(v) => v.software_version,
+        }
+    }
+
+    /// Returns the version of the protocol spoken by the given ER node
+    pub fn protocol_version(&self) -> SemVer {
+        match self {
+            Self::V1(v) => v.protocol_version,
+        }
+    }
+
+    /// Returns upcoming maintenance windows of the given ER node
+    pub fn maintenance(&self) -> &[MaintenanceWindow] {
+        match self {
+            Self::V1(v) => &v.maintenance,
         }
     }
 
     /// Returns load average of the given ER node
     pub fn load_average(&self) -> u32 {
         match self {
-            Self::V0(v) => v.load_average,
+            Self::V1(v) => v.load_average,
         }
     }
 
+    /// Returns structured load metrics of the given ER node
+    pub fn metrics(&self) -> &LoadMetrics {
+        match self {
+            Self::V1(v) => &v.metrics,
+        }
+    }
+
+    /// Returns normalized utilization of the given ER node in basis points,
+    /// see [LoadMetrics::utilization]
+    pub fn utilization(&self) -> u16 {
+        self.metrics().utilization()
+    }
+
+    /// Returns true if the given ER node accepts new delegations at given unix timestamp,
+    /// i.e. it's effectively active (not draining or offline) and isn't at full capacity
+    pub fn accepting_delegations(&self, now: i64) -> bool {
+        self.effective_status(now) == ErStatus::Active
+            && self.metrics().remaining_capacity() != Some(0)
+    }
+
     /// Returns 3 digit country code of the given ER node
     pub fn country_code(&self) -> CountryCode {
         match self {
-            Self::V0(v) => v.country_code,
+            Self::V1(v) => v.country_code,
+        }
+    }
+
+    /// Returns geographic region of the given ER node, if announced
+    pub fn region(&self) -> Option<Region> {
+        match self {
+            Self::V1(v) => v.region,
+        }
+    }
+
+    /// Returns approximate location of the given ER node, if announced
+    pub fn location(&self) -> Option<&GeoPoint> {
+        match self {
+            Self::V1(v) => v.location.as_ref(),
+        }
+    }
+
+    /// Returns the sequence number of the last sync applied to the given ER record
+    pub fn sequence(&self) -> u64 {
+        match self {
+            Self::V1(v) => v.sequence,
+        }
+    }
+
+    /// Returns unix timestamp, after which the exit of the given ER node can be completed
+    pub fn exit_after(&self) -> Option<i64> {
+        match self {
+            Self::V1(v) => v.exit_after,
+        }
+    }
+
+    /// Starts the exit of the given ER node, which can be completed after given unix timestamp
+    pub fn begin_exit(&mut self, exit_after: i64) {
+        match self {
+            Self::V1(v) => {
+                v.status = ErStatus::Deregistering;
+                v.exit_after = Some(exit_after);
+            }
+        }
+    }
+
+    /// Returns hardware attestation of the given ER node, if published
+    pub fn tee(&self) -> Option<&TeeAttestation> {
+        match self {
+            Self::V1(v) => v.tee.as_ref(),
+        }
+    }
+
+    /// Returns true if the given ER node advertises running inside of TEE
+    /// and its attestation hasn't expired at given unix timestamp
+    pub fn tee_attested(&self, now: i64) -> bool {
+        self.features().contains(Feature::TeeAttestation)
+            && self.tee().is_some_and(|t| !t.is_expired(now))
+    }
+
+    /// Updates hardware attestation of the given ER node
+    pub fn set_tee(&mut self, tee: Option<TeeAttestation>) {
+        match self {
+            Self::V1(v) => v.tee = tee,
+        }
+    }
+
+    /// Returns the slot, at which the given ER node was registered
+    pub fn registered_slot(&self) -> u64 {
+        match self {
+            Self::V1(v) => v.registered_slot,
+        }
+    }
+
+    /// Returns history of recent heartbeats (syncs) of the given ER node
+    pub fn heartbeats(&self) -> &Heartbeats {
+        match self {
+            Self::V1(v) => &v.heartbeats,
+        }
+    }
+
+    /// Marks the given ER record as registered at given slot, resetting heartbeat history
+    pub fn set_registered_slot(&mut self, slot: u64) {
+        match self {
+            Self::V1(v) => {
+                v.registered_slot = slot;
+                v.heartbeats = Heartbeats::default();
+                v.heartbeats.record(slot);
+            }
+        }
+    }
+
+    /// Records heartbeat of the given ER node at given slot
+    pub fn heartbeat(&mut self, slot: u64) {
+        match self {
+            Self::V1(v) => v.heartbeats.record(slot),
         }
     }
 
     /// Updates the FQDN address in the given ER record
     pub fn set_addr(&mut self, addr: String) {
         match self {
-            Self::V0(v) => v.addr = addr,
+            Self::V1(v) => v.addr = addr,
+        }
+    }
+
+    /// Updates (or removes) TLS certificate fingerprint in the given ER record
+    pub fn set_tls_fingerprint(&mut self, fingerprint: Option<SpkiFingerprint>) {
+        match self {
+            Self::V1(v) => v.tls_fingerprint = fingerprint,
+        }
+    }
+
+    /// Updates fee schedule in the given ER record
+    pub fn set_fees(&mut self, fees: FeeSchedule) {
+        match self {
+            Self::V1(v) => v.fees = fees,
         }
     }
 
-    /// Updates base transaction fee in the given ER record
-    pub fn set_base_fee(&mut self, base_fee: u16) {
+    /// Updates announced fee increase in the given ER record
+    pub fn set_pending_fees(&mut self, pending: Option<PendingFees>) {
         match self {
-            Self::V0(v) => v.base_fee = base_fee,
+            Self::V1(v) => v.pending_fees = pending,
+        }
+    }
+
+    /// Promotes pending fee schedule to the current one, if it's in force at given slot
+    pub fn settle_fees(&mut self, slot: u64) {
+        let Self::V1(v) = self;
+        if v.pending_fees
+            .as_ref()
+            .is_some_and(|p| p.effective_slot <= slot)
+        {
+            v.fees = v.pending_fees.take().unwrap().fees;
+        }
+    }
+
+    /// Announces new fee schedule at given slot, which replaces the current one immediately
+    /// if it doesn't raise any of the fees, otherwise it takes effect after notice period,
+    /// in both cases any previously pending increase is superseded, unless it's re-announced
+    pub fn announce_fees(&mut self, fees: FeeSchedule, slot: u64, notice_slots: u64) {
+        self.settle_fees(slot);
+        if self.pending_fees().is_some_and(|p| p.fees == fees) {
+            return;
+        }
+        if fees.raises(self.fees()) {
+            let effective_slot = slot.saturating_add(notice_slots);
+            self.set_pending_fees(Some(PendingFees {
+                fees,
+                effective_slot,
+            }));
+        } else {
+            self.set_fees(fees);
+            self.set_pending_fees(None);
         }
     }
 
     /// Updates the features set in the given ER record
     pub fn set_features(&mut self, features: FeaturesSet) {
         match self {
-            Self::V0(v) => v.features = features,
+            Self::V1(v) => v.features = features,
         }
     }
 
     /// Updates block time in ms in the given ER record
     pub fn set_block_time_ms(&mut self, block_time_ms: u16) {
         match self {
-            Self::V0(v) => v.block_time_ms = block_time_ms,
+            Self::V1(v) => v.block_time_ms = block_time_ms,
         }
     }
 
     /// Updates node status for the given ER record
     pub fn set_status(&mut self, status: ErStatus) {
         match self {
-            Self::V0(v) => v.status = status,
+            Self::V1(v) => v.status = status,
         }
     }
 
     /// Updates the load average for the given ER record
     pub fn set_load_average(&mut self, load_average: u32) {
         match self {
-            Self::V0(v) => v.load_average = load_average,
+            Self::V1(v) => v.load_average = load_average,
+        }
+    }
+
+    /// Updates structured load metrics for the given ER record
+    pub fn set_metrics(&mut self, metrics: LoadMetrics) {
+        match self {
+            Self::V1(v) => v.metrics = metrics,
         }
     }
 
     /// Updates the country code for the given ER record
     pub fn set_country_code(&mut self, country_code: CountryCode) {
         match self {
-            Self::V0(v) => v.country_code = country_code,
+            Self::V1(v) => v.country_code = country_code,
+        }
+    }
+
+    /// Updates (or removes) geographic region for the given ER record
+    pub fn set_region(&mut self, region: Option<Region>) {
+        match self {
+            Self::V1(v) => v.region = region,
+        }
+    }
+
+    /// Updates (or removes) approximate location for the given ER record
+    pub fn set_location(&mut self, location: Option<GeoPoint>) {
+        match self {
+            Self::V1(v) => v.location = location,
+        }
+    }
+
+    /// Updates validator software version for the given ER record
+    pub fn set_software_version(&mut self, version: SemVer) {
+        match self {
+            Self::V1(v) => v.software_version = version,
+        }
+    }
+
+    /// Updates protocol version for the given ER record
+    pub fn set_protocol_version(&mut self, version: SemVer) {
+        match self {
+            Self::V1(v) => v.protocol_version = version,
+        }
+    }
+
+    /// Replaces the maintenance windows in the given ER record
+    pub fn set_maintenance(&mut self, maintenance: Vec<MaintenanceWindow>) {
+        match self {
+            Self::V1(v) => v.maintenance = maintenance,
+        }
+    }
+
+    /// Updates the sequence number of the last applied sync for the given ER record
+    pub fn set_sequence(&mut self, sequence: u64) {
+        match self {
+            Self::V1(v) => v.sequence = sequence,
         }
     }
 }
@@ -130,6 +473,13 @@ impl ErRecord {
 #[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CountryCode([u8; 3]);
 
+impl CountryCode {
+    /// Returns raw bytes of the country code
+    pub fn as_bytes(&self) -> &[u8; 3] {
+        &self.0
+    }
+}
+
 impl<S: AsRef<[u8]>> From<S> for CountryCode {
     fn from(value: S) -> Self {
         const LEN: usize = std::mem::size_of::<CountryCode>();
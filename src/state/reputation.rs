@@ -0,0 +1,167 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::{consts::ER_REPUTATION_SEED, ID};
+
+use super::{attestation::AttestationRecord, metrics::MAX_BPS, record::ErRecord};
+
+/// Number of slots in a single heartbeat period, roughly an hour, ER node
+/// is considered to be up during the period if it synced at least once
+pub const HEARTBEAT_PERIOD_SLOTS: u64 = 9_000;
+
+/// Number of the most recent heartbeat periods, which uptime is computed over
+pub const HEARTBEAT_PERIODS: u64 = u32::BITS as u64;
+
+/// Age of the record in slots (roughly 30 days), after which it gets full age score
+pub const REPUTATION_MATURITY_SLOTS: u64 = 30 * 24 * HEARTBEAT_PERIOD_SLOTS;
+
+/// Weights of uptime, attestation success ratio and record age in the score, in basis points
+const UPTIME_WEIGHT: u64 = 4_000;
+const SUCCESS_WEIGHT: u64 = 4_000;
+const AGE_WEIGHT: u64 = 2_000;
+
+/// History of ER node heartbeats (syncs) over the recent periods
+#[derive(Debug, Default, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Heartbeats {
+    /// Index of the last period, in which heartbeat was seen
+    pub latest: u64,
+    /// Bit map of periods with heartbeats, the lowest bit being the latest period
+    pub bitmap: u32,
+}
+
+/// Reputation score of ER node, updated by permissionless crank
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct ReputationRecord {
+    /// Identity of ER node
+    pub identity: Pubkey,
+    /// Score in basis points, see [score]
+    pub score: u16,
+    /// Slot, at which the score was computed
+    pub slot: u64,
+}
+
+/// Inputs of reputation score computation
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReputationInputs {
+    /// Share of recent heartbeat periods, in which ER node synced, in basis points
+    pub uptime_bps: u16,
+    /// Share of successful requests observed by clients in basis points, if any were observed
+    pub success_rate_bps: Option<u16>,
+    /// Number of slots passed since ER node registration
+    pub age_slots: u64,
+}
+
+impl Heartbeats {
+    /// Records heartbeat at given slot
+    pub fn record(&mut self, slot: u64) {
+        let period = slot / HEARTBEAT_PERIOD_SLOTS;
+        if period >= self.latest {
+            let shift = period - self.latest;
+            self.bitmap = if shift < HEARTBEAT_PERIODS {
+                self.bitmap << shift
+            } else {
+                0
+            };
+            self.latest = period;
+            self.bitmap |= 1;
+        } else if self.latest - period < HEARTBEAT_PERIODS {
+            self.bitmap |= 1 << (self.latest - period);
+        }
+    }
+
+    /// Returns share of recent periods (since registration at given slot), in which
+    /// heartbeat was seen, in basis points, the current period is only taken into
+    /// account once heartbeat is seen in it, so that it isn't counted as missed early
+    pub fn uptime_bps(&self, slot: u64, registered_slot: u64) -> u16 {
+        let current = slot / HEARTBEAT_PERIOD_SLOTS;
+        let first = registered_slot / HEARTBEAT_PERIOD_SLOTS;
+        // bit map aligned, so that the lowest bit is the current period
+        let bitmap = match current.checked_sub(self.latest) {
+            Some(shift) if shift < HEARTBEAT_PERIODS => (self.bitmap as u64) << shift,
+            _ => 0,
+        };
+        let mut periods = (current.saturating_sub(first) + 1).min(HEARTBEAT_PERIODS);
+        let mut bitmap = bitmap & ((1u64 << periods) - 1);
+        if bitmap & 1 == 0 && periods > 1 {
+            periods -= 1;
+            bitmap >>= 1;
+        }
+        (bitmap.count_ones() as u64 * MAX_BPS as u64 / periods) as u16
+    }
+}
+
+impl ReputationRecord {
+    /// Computes reputation record's PDA for the given ER node identity
+    pub fn pda(identity: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ER_REPUTATION_SEED, identity.as_ref()], &ID)
+    }
+}
+
+impl ReputationInputs {
+    /// Gathers score inputs at given slot out of ER node's record and attestations
+    pub fn collect(record: &ErRecord, attestation: Option<&AttestationRecord>, slot: u64) -> Self {
+        Self {
+            uptime_bps: record
+                .heartbeats()
+                .uptime_bps(slot, record.registered_slot()),
+            success_rate_bps: attestation.and_then(|a| a.stats(slot).success_rate_bps()),
+            age_slots: slot.saturating_sub(record.registered_slot()),
+        }
+    }
+}
+
+/// Computes deterministic reputation score in basis points, as weighted sum of uptime (40%),
+/// attestation success ratio (40%) and record age relative to [REPUTATION_MATURITY_SLOTS]
+/// (20%), nodes without any attestations get neutral half of the success ratio weight
+pub fn score(inputs: &ReputationInputs) -> u16 {
+    let uptime = inputs.uptime_bps.min(MAX_BPS) as u64;
+    let success = inputs.success_rate_bps.unwrap_or(MAX_BPS / 2).min(MAX_BPS) as u64;
+    let age = inputs.age_slots.min(REPUTATION_MATURITY_SLOTS) * MAX_BPS as u64
+        / REPUTATION_MATURITY_SLOTS;
+    let weighted = uptime * UPTIME_WEIGHT + success * SUCCESS_WEIGHT + age * AGE_WEIGHT;
+    (weighted / MAX_BPS as u64) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uptime() {
+        let registered = 5 * HEARTBEAT_PERIOD_SLOTS;
+        let mut heartbeats = Heartbeats::default();
+        heartbeats.record(registered);
+        assert_eq!(heartbeats.uptime_bps(registered, registered), MAX_BPS);
+        // heartbeats in periods 6 and 8, while 7 is missed, 9 isn't over yet
+        heartbeats.record(6 * HEARTBEAT_PERIOD_SLOTS);
+        heartbeats.record(8 * HEARTBEAT_PERIOD_SLOTS + 1);
+        assert_eq!(
+            heartbeats.uptime_bps(9 * HEARTBEAT_PERIOD_SLOTS, registered),
+            7_500
+        );
+        // long silence
+        let slot = 100 * HEARTBEAT_PERIOD_SLOTS;
+        assert_eq!(heartbeats.uptime_bps(slot, registered), 0);
+        heartbeats.record(slot);
+        assert_eq!(
+            heartbeats.uptime_bps(slot, registered),
+            (MAX_BPS as u64 / HEARTBEAT_PERIODS) as u16
+        );
+    }
+
+    #[test]
+    fn test_score() {
+        let mut inputs = ReputationInputs {
+            uptime_bps: MAX_BPS,
+            success_rate_bps: Some(MAX_BPS),
+            age_slots: REPUTATION_MATURITY_SLOTS,
+        };
+        assert_eq!(score(&inputs), MAX_BPS);
+        inputs.age_slots = REPUTATION_MATURITY_SLOTS / 2;
+        assert_eq!(score(&inputs), 9_000);
+        inputs.success_rate_bps = None;
+        assert_eq!(score(&inputs), 7_000);
+        inputs.uptime_bps = 0;
+        assert_eq!(score(&inputs), 3_000);
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Semver-like version, ordered by major, minor and patch components
+#[derive(
+    Debug, Default, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub struct SemVer {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+}
+
+impl SemVer {
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parses version from `major.minor.patch` string, optionally prefixed with `v`
+    pub fn parse(version: &str) -> Option<Self> {
+        let version = version.trim();
+        let version = version.strip_prefix('v').unwrap_or(version);
+        let mut parts = version.split('.').map(str::parse::<u16>);
+        let major = parts.next()?.ok()?;
+        let minor = parts.next()?.ok()?;
+        let patch = parts.next()?.ok()?;
+        parts
+            .next()
+            .is_none()
+            .then_some(Self::new(major, minor, patch))
+    }
+
+    /// Returns true if this version can serve clients, which require the given one,
+    /// i.e. it has the same major version and it's not older than the required one
+    pub fn is_compatible_with(&self, required: &SemVer) -> bool {
+        self.major == required.major && self >= required
+    }
+}
+
+impl fmt::Display for SemVer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semver() {
+        let version = SemVer::parse("v1.12.3").unwrap();
+        assert_eq!(version, SemVer::new(1, 12, 3));
+        assert_eq!(version.to_string(), "1.12.3");
+        assert_eq!(SemVer::parse("1.2"), None);
+        assert_eq!(SemVer::parse("1.2.3.4"), None);
+        assert!(SemVer::new(1, 9, 9) < version);
+        assert!(version.is_compatible_with(&SemVer::new(1, 2, 0)));
+        assert!(!version.is_compatible_with(&SemVer::new(1, 13, 0)));
+        assert!(!SemVer::new(2, 0, 0).is_compatible_with(&version));
+    }
+}
@@ -10,4 +10,7 @@ pub enum ErStatus {
     Draining = 1,
     /// node has been taken offline, e.g. for maintenance
     Offline = 2,
+    /// node is leaving the registry, it's hidden from discovery and its
+    /// record (along with the bond) is released once the cooldown passes
+    Deregistering = 3,
 }
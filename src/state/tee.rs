@@ -0,0 +1,199 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    hash::{hash, Hash},
+    pubkey::Pubkey,
+};
+
+use crate::{consts::ER_TEE_QUOTE_SEED, ID};
+
+/// Size of the quote header, preceding the report body in SGX and TDX DCAP quotes
+const DCAP_HEADER_LEN: usize = 48;
+/// Offset and length of MRENCLAVE in SGX quote, and the size of SGX report body
+const SGX_MRENCLAVE: (usize, usize) = (DCAP_HEADER_LEN + 64, 32);
+const SGX_REPORT_LEN: usize = 384;
+/// Offset and length of MRTD in TDX quote, and the size of TD report body
+const TDX_MRTD: (usize, usize) = (DCAP_HEADER_LEN + 136, 48);
+const TDX_REPORT_LEN: usize = 584;
+/// TEE type of TDX in the quote header
+const TDX_TEE_TYPE: u32 = 0x81;
+/// Offset and length of launch measurement in SEV-SNP attestation report, and its size
+const SNP_MEASUREMENT: (usize, usize) = (0x90, 48);
+const SNP_REPORT_LEN: usize = 0x4A0;
+
+/// Kind of hardware attestation quote
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+#[borsh(use_discriminant = true)]
+pub enum QuoteType {
+    /// Intel SGX DCAP quote (version 3 or 4)
+    SgxDcap = 0,
+    /// Intel TDX DCAP quote (version 4 or 5)
+    TdxDcap = 1,
+    /// AMD SEV-SNP attestation report
+    SevSnp = 2,
+    /// AWS Nitro enclave attestation document, opaque to the parser
+    Nitro = 3,
+}
+
+/// Summary of hardware attestation of ER node, kept in its record,
+/// while the raw quote is stored in a separate PDA, see [TeeQuoteRecord]
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct TeeAttestation {
+    pub quote_type: QuoteType,
+    /// Hash of the measurement register (MRENCLAVE, MRTD or SNP launch measurement),
+    /// identifying the code running inside of TEE, see [measurement_hash], zeroed
+    /// for Nitro documents, which have to be parsed off chain
+    pub measurement: Hash,
+    /// Hash of the raw quote, see [quote_hash]
+    pub quote_hash: Hash,
+    /// Unix timestamp (seconds), after which the attestation should no longer be trusted
+    pub expires_at: i64,
+}
+
+/// Raw attestation quote of ER node, uploaded in chunks
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct TeeQuoteRecord {
+    /// Identity of ER node
+    pub identity: Pubkey,
+    /// Raw quote bytes, to be verified off chain against vendor's certificate chain
+    pub quote: Vec<u8>,
+}
+
+/// Chunk of raw quote being uploaded
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq)]
+pub struct QuoteChunk {
+    /// Total length of the quote, the quote record is resized to it if necessary
+    pub len: u32,
+    /// Offset of the chunk in the quote
+    pub offset: u32,
+    pub bytes: Vec<u8>,
+}
+
+/// Arguments of publishing uploaded quote into ER node's record
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct QuotePublication {
+    pub quote_type: QuoteType,
+    /// Unix timestamp (seconds), after which the attestation should no longer be trusted
+    pub expires_at: i64,
+}
+
+impl TeeAttestation {
+    /// Returns true if attestation is no longer to be trusted at given unix timestamp
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at <= now
+    }
+
+    /// Returns true if the given raw quote is the one attestation was published for
+    pub fn matches(&self, quote: &[u8]) -> bool {
+        quote_hash(quote) == self.quote_hash
+    }
+}
+
+impl TeeQuoteRecord {
+    /// Computes quote record's PDA for the given ER node identity
+    pub fn pda(identity: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ER_TEE_QUOTE_SEED, identity.as_ref()], &ID)
+    }
+}
+
+/// Returns the hash of raw quote bytes
+pub fn quote_hash(quote: &[u8]) -> Hash {
+    hash(quote)
+}
+
+/// Extracts the raw measurement register out of the quote of given type, performing only
+/// structural checks (version, size), signatures are to be verified off chain, returns
+/// `None` if quote is malformed or its type (Nitro) cannot be parsed
+pub fn parse_measurement(quote_type: QuoteType, quote: &[u8]) -> Option<&[u8]> {
+    let u16_at = |offset: usize| {
+        Some(u16::from_le_bytes(
+            quote.get(offset..offset + 2)?.try_into().ok()?,
+        ))
+    };
+    let u32_at = |offset: usize| {
+        Some(u32::from_le_bytes(
+            quote.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+    let (valid, (offset, len), min_len) = match quote_type {
+        QuoteType::SgxDcap => (
+            matches!(u16_at(0)?, 3 | 4),
+            SGX_MRENCLAVE,
+            DCAP_HEADER_LEN + SGX_REPORT_LEN,
+        ),
+        QuoteType::TdxDcap => (
+            matches!(u16_at(0)?, 4 | 5) && u32_at(4)? == TDX_TEE_TYPE,
+            TDX_MRTD,
+            DCAP_HEADER_LEN + TDX_REPORT_LEN,
+        ),
+        QuoteType::SevSnp => (u32_at(0)? >= 2, SNP_MEASUREMENT, SNP_REPORT_LEN),
+        QuoteType::Nitro => return None,
+    };
+    if !valid || quote.len() < min_len {
+        return None;
+    }
+    quote.get(offset..offset + len)
+}
+
+/// Returns the hash of measurement register extracted from the quote, see [parse_measurement]
+pub fn measurement_hash(quote_type: QuoteType, quote: &[u8]) -> Option<Hash> {
+    parse_measurement(quote_type, quote).map(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sgx_quote() {
+        let mut quote = vec![0u8; DCAP_HEADER_LEN + SGX_REPORT_LEN + 64];
+        quote[..2].copy_from_slice(&3u16.to_le_bytes());
+        quote[SGX_MRENCLAVE.0..SGX_MRENCLAVE.0 + 32].fill(0xAB);
+        assert_eq!(
+            parse_measurement(QuoteType::SgxDcap, &quote),
+            Some(&[0xAB; 32][..])
+        );
+        assert_eq!(
+            measurement_hash(QuoteType::SgxDcap, &quote),
+            Some(hash(&[0xAB; 32]))
+        );
+        // wrong version or truncated quote
+        assert_eq!(parse_measurement(QuoteType::TdxDcap, &quote), None);
+        assert_eq!(parse_measurement(QuoteType::SgxDcap, &quote[..400]), None);
+        assert_eq!(parse_measurement(QuoteType::Nitro, &quote), None);
+    }
+
+    #[test]
+    fn test_parse_tdx_and_snp_quotes() {
+        let mut quote = vec![0u8; DCAP_HEADER_LEN + TDX_REPORT_LEN];
+        quote[..2].copy_from_slice(&4u16.to_le_bytes());
+        quote[4..8].copy_from_slice(&TDX_TEE_TYPE.to_le_bytes());
+        quote[TDX_MRTD.0..TDX_MRTD.0 + 48].fill(1);
+        assert_eq!(
+            parse_measurement(QuoteType::TdxDcap, &quote),
+            Some(&[1; 48][..])
+        );
+
+        let mut report = vec![0u8; SNP_REPORT_LEN];
+        report[..4].copy_from_slice(&2u32.to_le_bytes());
+        report[SNP_MEASUREMENT.0..SNP_MEASUREMENT.0 + 48].fill(2);
+        assert_eq!(
+            parse_measurement(QuoteType::SevSnp, &report),
+            Some(&[2; 48][..])
+        );
+    }
+
+    #[test]
+    fn test_attestation_expiry() {
+        let quote = [7u8; 100];
+        let attestation = TeeAttestation {
+            quote_type: QuoteType::Nitro,
+            measurement: Hash::default(),
+            quote_hash: quote_hash(&quote),
+            expires_at: 1_000,
+        };
+        assert!(!attestation.is_expired(999));
+        assert!(attestation.is_expired(1_000));
+        assert!(attestation.matches(&quote));
+        assert!(!attestation.matches(&quote[1..]));
+    }
+}
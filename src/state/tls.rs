@@ -0,0 +1,64 @@
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::hash::hash;
+
+/// SHA-256 fingerprint of DER encoded SubjectPublicKeyInfo of TLS certificate
+/// served by ER node, which lets clients pin connections made over raw IPs
+#[derive(Debug, BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpkiFingerprint(pub [u8; 32]);
+
+impl SpkiFingerprint {
+    /// Computes fingerprint of the given DER encoded SubjectPublicKeyInfo
+    pub fn of(spki_der: &[u8]) -> Self {
+        Self(hash(spki_der).to_bytes())
+    }
+
+    /// Parses fingerprint from hex string, optionally separated by colons,
+    /// as printed by `openssl x509 -pubkey | openssl pkey -pubin -outform der | sha256sum`
+    pub fn parse(hex: &str) -> Option<Self> {
+        let digits: Vec<u8> = hex.bytes().filter(|&b| b != b':').collect();
+        if digits.len() != 64 {
+            return None;
+        }
+        let mut bytes = [0; 32];
+        for (byte, pair) in bytes.iter_mut().zip(digits.chunks(2)) {
+            // from_str_radix alone would accept sign prefixes, e.g. "+f"
+            if !pair.iter().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            let pair = std::str::from_utf8(pair).ok()?;
+            *byte = u8::from_str_radix(pair, 16).ok()?;
+        }
+        Some(Self(bytes))
+    }
+}
+
+impl fmt::Display for SpkiFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{b:02x}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_hex() {
+        let fingerprint = SpkiFingerprint::of(b"spki");
+        let hex = fingerprint.to_string();
+        assert_eq!(hex.len(), 64);
+        assert_eq!(SpkiFingerprint::parse(&hex), Some(fingerprint));
+        let colons = hex
+            .as_bytes()
+            .chunks(2)
+            .map(|p| std::str::from_utf8(p).unwrap())
+            .collect::<Vec<_>>()
+            .join(":");
+        assert_eq!(SpkiFingerprint::parse(&colons), Some(fingerprint));
+        assert_eq!(SpkiFingerprint::parse(&hex[2..]), None);
+        assert_eq!(SpkiFingerprint::parse(&"zz".repeat(32)), None);
+        assert_eq!(SpkiFingerprint::parse(&format!("+f{}", &hex[2..])), None);
+    }
+}
@@ -0,0 +1,112 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{hash::Hash, pubkey::Pubkey};
+
+use crate::state::{
+    features::FeaturesSet,
+    fees::{FeeSchedule, PendingFees},
+    geo::{GeoPoint, Region},
+    maintenance::MaintenanceWindow,
+    metrics::LoadMetrics,
+    record::CountryCode,
+    reputation::Heartbeats,
+    semver::SemVer,
+    status::ErStatus,
+    tee::TeeAttestation,
+    tls::SpkiFingerprint,
+};
+
+use super::v0::RecordV0;
+
+/// Version 1 of ER domain registry record
+#[derive(Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone)]
+pub struct RecordV1 {
+    /// Identity of ER node (pubkey from its keypair)
+    pub identity: Pubkey,
+    /// Key allowed to update the record in batches along with other ER nodes,
+    /// e.g. operator of ER fleet, can be set to identity itself if not needed
+    pub authority: Pubkey,
+    /// Genesis hash of the base chain cluster, which ER node settles to
+    pub cluster: Hash,
+    /// Identifier of the chain run by ER node
+    pub chain_id: u64,
+    /// Current status of ER node
+    pub status: ErStatus,
+    /// Block time of given ER node in ms
+    pub block_time_ms: u16,
+    /// A bitmap of all possible combination of custom features that the ER node supports
+    pub features: FeaturesSet,
+    /// An average value, which is acts as an indicator
+    /// of how loaded the given ER node currently is,
+    /// see `metrics` for more detailed breakdown
+    pub load_average: u32,
+    /// Structured load metrics, giving insight into what exactly the ER node is bound by
+    pub metrics: LoadMetrics,
+    /// 3 digit country code, where ER node is deployed
+    /// IBM spec was used as standard: https://www.ibm.com/docs/en/sia?topic=r-country-region-codes
+    pub country_code: CountryCode,
+    /// Version of validator software run by ER node
+    pub software_version: SemVer,
+    /// Version of the protocol spoken by ER node
+    pub protocol_version: SemVer,
+    /// Sequence number of the last applied sync, only strictly greater ones are accepted
+    pub sequence: u64,
+    /// Slot, at which ER node was registered, set by the program
+    pub registered_slot: u64,
+    /// History of recent syncs, serving as heartbeats of ER node, maintained by the program
+    pub heartbeats: Heartbeats,
+    /// Pricing of transactions executed by ER node
+    pub fees: FeeSchedule,
+    /// Fee increase announced by ER node, which doesn't apply until the notice period passes
+    pub pending_fees: Option<PendingFees>,
+    /// Unix timestamp (seconds), after which ER node's exit can be completed, set once it's started
+    pub exit_after: Option<i64>,
+    /// Hardware attestation of ER node running inside of TEE, if published
+    pub tee: Option<TeeAttestation>,
+    /// Fingerprint of TLS certificate's public key served at `addr`, if pinned
+    pub tls_fingerprint: Option<SpkiFingerprint>,
+    /// Geographic region of ER node, if announced
+    pub region: Option<Region>,
+    /// Approximate location of ER node, if announced
+    pub location: Option<GeoPoint>,
+    /// Variable length string representing FQDN
+    pub addr: String,
+    /// Upcoming maintenance windows announced by ER node
+    pub maintenance: Vec<MaintenanceWindow>,
+}
+
+/// Upgrades the record registered before versioned fields were introduced, the fields
+/// unknown to the legacy layout are left at their defaults (e.g. zeroed cluster hash),
+/// until ER node syncs them, the authority is set to the identity itself
+impl From<RecordV0> for RecordV1 {
+    fn from(v: RecordV0) -> Self {
+        Self {
+            identity: v.identity,
+            authority: v.identity,
+            cluster: Hash::default(),
+            chain_id: 0,
+            status: v.status,
+            block_time_ms: v.block_time_ms,
+            features: v.features,
+            load_average: v.load_average,
+            metrics: LoadMetrics::default(),
+            country_code: v.country_code,
+            software_version: SemVer::default(),
+            protocol_version: SemVer::default(),
+            sequence: 0,
+            registered_slot: 0,
+            heartbeats: Heartbeats::default(),
+            fees: FeeSchedule {
+                lamports_per_signature: v.base_fee.into(),
+                ..Default::default()
+            },
+            pending_fees: None,
+            exit_after: None,
+            tee: None,
+            tls_fingerprint: None,
+            region: None,
+            location: None,
+            addr: v.addr,
+            maintenance: vec![],
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Compute units benchmark of every instruction, writes the report into
+//! `target/tmp/cu-report.csv` and fails if any of the instructions consumes more
+//! than allowed by the baseline committed in `tests/cu_baseline.csv`, run with
+//! `UPDATE_CU_BASELINE=1` to replace the baseline with the measured usage
+
+use std::{collections::HashMap, fmt::Write};
+
+use common::{TestEnv, UNBONDING_COOLDOWN_SECS};
+use mdp::state::{
+    attestation::Observation,
+    dispute::{DisputeClaim, DisputeReason, Ruling, SlashRecipient},
+    status::ErStatus,
+    tee::{QuoteChunk, QuotePublication, QuoteType},
+};
+use program_test::BanksClient;
+use sdk::{
+    clock::Clock, hash::Hash, native_token::LAMPORTS_PER_SOL, signature::Keypair, signer::Signer,
+    system_instruction, transaction::Transaction,
+};
+
+pub mod common;
+
+const BASELINE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cu_baseline.csv");
+const REPORT: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/cu-report.csv");
+const HEADER: &str = "instruction,payload,units";
+
+/// Regression, which is tolerated over the baseline, in basis points
+const TOLERANCE_BPS: u64 = 500;
+
+/// Lengths of ER node address, registration and sync are measured with
+const ADDR_LENS: [usize; 3] = [32, 64, 256];
+
+/// Compute units consumed by instruction, keyed by its name and payload size
+/// (address length, name length, chunk size or number of batched updates)
+#[derive(Default)]
+struct Report(Vec<(String, usize, u64)>);
+
+impl Report {
+    /// Records compute units consumed by the last transaction sent by the helpers
+    fn record(&mut self, instruction: &str, payload: usize) {
+        let units = common::last_compute_units().expect("measurement should be enabled");
+        self.0.push((instruction.to_string(), payload, units));
+    }
+
+    fn to_csv(&self) -> String {
+        self.0
+            .iter()
+            .fold(format!("{HEADER}\n"), |mut csv, (ix, payload, units)| {
+                writeln!(csv, "{ix},{payload},{units}").unwrap();
+                csv
+            })
+    }
+}
+
+fn parse_baseline(csv: &str) -> HashMap<(String, usize), u64> {
+    csv.lines()
+        .skip(1)
+        .filter(|l| !l.trim().is_empty())
+        .map(|line| {
+            let fields: Vec<_> = line.split(',').collect();
+            let [ix, payload, units] = fields[..] else {
+                panic!("malformed baseline entry: {line}");
+            };
+            let payload = payload.parse().expect("payload should be a number");
+            let units = units.parse().expect("units should be a number");
+            ((ix.to_string(), payload), units)
+        })
+        .collect()
+}
+
+/// Address of ER node of exactly the given length
+fn addr(len: usize, port: u16) -> String {
+    let prefix = format!("https://{port}.");
+    let suffix = ".example.com/";
+    let filler = len - prefix.len() - suffix.len();
+    format!("{prefix}{}{suffix}", "a".repeat(filler))
+}
+
+async fn fund(banks: &mut BanksClient, payer: &Keypair) -> Keypair {
+    let keypair = Keypair::new();
+    let ix = system_instruction::transfer(&payer.pubkey(), &keypair.pubkey(), LAMPORTS_PER_SOL);
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], hash);
+    let result = banks.process_transaction(tx).await;
+    assert_ok!(result, "error funding ER node identity {}");
+    keypair
+}
+
+#[tokio::test]
+async fn bench_compute_units() {
+    common::measure_compute_units();
+    let TestEnv {
+        mut context,
+        mut banks,
+        payer,
+        admin,
+        identity,
+        ..
+    } = common::setup().await;
+    let mut report = Report::default();
+    report.record("InitConfig", 0);
+
+    let mut identities = vec![identity];
+    while identities.len() < ADDR_LENS.len() {
+        identities.push(fund(&mut banks, &payer).await);
+    }
+
+    for (identity, len) in identities.iter().zip(ADDR_LENS) {
+        let mut record = common::test_record(identity.pubkey(), payer.pubkey());
+        record.set_addr(addr(len, 9324));
+        let result = common::register(&mut banks, record, identity).await;
+        assert_ok!(result, "error processing register transaction {}");
+        report.record("Register", len);
+    }
+
+    for (identity, len) in identities.iter().zip(ADDR_LENS) {
+        let pda = common::record_pda(&identity.pubkey());
+        let mut record = common::load_record(&mut banks, pda).await.unwrap();
+        record.set_addr(addr(len, 9325));
+        record.set_sequence(1);
+        let result = common::sync(&mut banks, identity, record).await;
+        assert_ok!(result, "error processing sync transaction {}");
+        report.record("Sync", len);
+    }
+
+    let identity = &identities[0];
+    let pda = common::record_pda(&identity.pubkey());
+    let mut record = common::load_record(&mut banks, pda).await.unwrap();
+    record.set_sequence(2);
+    let len = record.addr().len();
+    let result = common::relayed_sync(&mut banks, &payer, identity, record).await;
+    assert_ok!(result, "error processing relayed sync transaction {}");
+    report.record("RelayedSync", len);
+
+    let batch = identities
+        .iter()
+        .map(|id| common::status_update(id.pubkey(), 3, ErStatus::Draining))
+        .collect();
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx = common::batch_sync_transaction(&payer, batch, hash);
+    let result = common::process(&mut banks, tx).await;
+    assert_ok!(result, "error processing batch sync transaction {}");
+    report.record("BatchSync", identities.len());
+
+    const NAME: &str = "bench";
+    let result = common::claim_name(&mut banks, identity, NAME, None, Some(identity)).await;
+    assert_ok!(result, "error processing claim name transaction {}");
+    report.record("ClaimName", NAME.len());
+    let result = common::transfer_name(&mut banks, identity, NAME, payer.pubkey()).await;
+    assert_ok!(result, "error processing transfer name transaction {}");
+    report.record("TransferName", NAME.len());
+    let result = common::release_name(&mut banks, &payer, NAME, Some(identity.pubkey())).await;
+    assert_ok!(result, "error processing release name transaction {}");
+    report.record("ReleaseName", NAME.len());
+
+    let result = banks.get_sysvar::<Clock>().await;
+    let clock = assert_ok!(result, "error querying clock sysvar {}");
+    let observation = Observation {
+        identity: identity.pubkey(),
+        slot: clock.slot,
+        latency_ms: 30,
+        success: true,
+    };
+    let result = common::attest(&mut banks, &admin, observation).await;
+    assert_ok!(result, "error processing attest transaction {}");
+    report.record("Attest", 0);
+    let result = common::update_reputation(&mut banks, &payer, identity.pubkey()).await;
+    assert_ok!(result, "error processing update reputation transaction {}");
+    report.record("UpdateReputation", 0);
+
+    let stake = LAMPORTS_PER_SOL / 10;
+    let result = common::bond(&mut banks, identity, stake).await;
+    assert_ok!(result, "error processing bond transaction {}");
+    report.record("Bond", 0);
+    let claim = DisputeClaim {
+        identity: identity.pubkey(),
+        reason: DisputeReason::WrongEndpoint,
+        evidence: Hash::new_from_array([9; 32]),
+        stake,
+    };
+    let result = common::open_dispute(&mut banks, &payer, claim).await;
+    assert_ok!(result, "error processing open dispute transaction {}");
+    report.record("OpenDispute", 0);
+    let ruling = Ruling {
+        upheld: false,
+        slash_to: SlashRecipient::Treasury,
+    };
+    let challenger = payer.pubkey();
+    let result =
+        common::resolve_dispute(&mut banks, &admin, identity.pubkey(), challenger, ruling).await;
+    assert_ok!(result, "error processing resolve dispute transaction {}");
+    report.record("ResolveDispute", 0);
+
+    let mut quote = vec![0u8; 1_200];
+    quote[..2].copy_from_slice(&3u16.to_le_bytes());
+    for (i, bytes) in quote.chunks(600).enumerate() {
+        let chunk = QuoteChunk {
+            len: quote.len() as u32,
+            offset: (i * 600) as u32,
+            bytes: bytes.to_vec(),
+        };
+        let result = common::write_tee_quote(&mut banks, identity, chunk).await;
+        assert_ok!(result, "error processing write quote transaction {}");
+        // keyed by the end of the chunk, the first one also creates the quote record
+        report.record("WriteTeeQuote", i * 600 + bytes.len());
+    }
+    let publication = QuotePublication {
+        quote_type: QuoteType::SgxDcap,
+        expires_at: clock.unix_timestamp + 3600,
+    };
+    let result = common::publish_tee_quote(&mut banks, identity, publication).await;
+    assert_ok!(result, "error processing publish quote transaction {}");
+    report.record("PublishTeeQuote", quote.len());
+
+    let result = common::update_config(&mut banks, &admin, common::test_config(&admin)).await;
+    assert_ok!(result, "error processing update config transaction {}");
+    report.record("UpdateConfig", 0);
+
+    let len = ADDR_LENS[0];
+    let result = common::begin_unregister(&mut banks, identity, pda).await;
+    assert_ok!(result, "error processing begin unregister transaction {}");
+    report.record("BeginUnregister", len);
+    common::advance_clock(&mut context, UNBONDING_COOLDOWN_SECS).await;
+    let result = common::complete_unregister(&mut banks, identity, pda).await;
+    assert_ok!(
+        result,
+        "error processing complete unregister transaction {}"
+    );
+    report.record("CompleteUnregister", len);
+
+    let csv = report.to_csv();
+    std::fs::write(REPORT, &csv).expect("failed to write compute units report");
+
+    if std::env::var_os("UPDATE_CU_BASELINE").is_some() {
+        std::fs::write(BASELINE, &csv).expect("failed to update compute units baseline");
+        return;
+    }
+    let baseline = std::fs::read_to_string(BASELINE).expect("failed to read baseline");
+    let baseline = parse_baseline(&baseline);
+    let regressions: Vec<_> = report
+        .0
+        .iter()
+        .filter_map(|(ix, payload, units)| {
+            let Some(&allowed) = baseline.get(&(ix.clone(), *payload)) else {
+                return Some(format!("{ix}({payload}): {units} without baseline"));
+            };
+            let limit = allowed + allowed * TOLERANCE_BPS / 10_000;
+            (*units > limit).then(|| format!("{ix}({payload}): {units} > {allowed}"))
+        })
+        .collect();
+    assert!(
+        regressions.is_empty(),
+        "compute units usage regressed past the baseline, or isn't covered by it (regenerate \
+         with UPDATE_CU_BASELINE=1): {regressions:?}"
+    );
+}
@@ -1,59 +1,283 @@
+#[macro_export]
+macro_rules! assert_ok {
+    ($result: ident, $errmsg: expr) => {{
+        assert!($result.is_ok(), $errmsg, $result.unwrap_err());
+        $result.unwrap()
+    }};
+}
+
+use std::cell::Cell;
+
+use borsh::BorshDeserialize;
 use mdp::{
-    instructions::{sync::SyncInstruction, version::v0::SyncRecordV0, Instruction},
+    consts::ER_RECORD_SEED,
+    error::MdpError,
+    instructions::{
+        relay::RelayedSync, sync::SyncInstruction, version::v1::SyncRecordV1, Instruction,
+    },
     state::{
+        attestation::{AttestationRecord, Observation},
+        bond::BondRecord,
+        config::Config,
+        dispute::{DisputeClaim, DisputeRecord, Ruling},
         features::{Feature, FeaturesSet},
+        fees::{FeatureSurcharge, FeeSchedule},
+        geo::{GeoPoint, Region},
+        index::{listed_pages, touched_pages, IndexKey, IndexPage},
+        metrics::LoadMetrics,
+        name::{parent_name, NameRecord, ReverseNameRecord},
         record::{CountryCode, ErRecord},
+        reputation::ReputationRecord,
+        semver::SemVer,
         status::ErStatus,
-        version::v0::RecordV0,
+        tee::{QuoteChunk, QuotePublication, TeeQuoteRecord},
+        version::v1::RecordV1,
     },
 };
-use program_test::{BanksClient, BanksClientError, ProgramTest};
+use program_test::{
+    BanksClient, BanksClientError, ProgramTest, ProgramTestBanksClientExt, ProgramTestContext,
+};
 use sdk::{
     account::Account,
-    instruction::{AccountMeta, Instruction as SolanaInstruction},
+    bpf_loader_upgradeable,
+    clock::Clock,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction as SolanaInstruction, InstructionError},
     native_token::LAMPORTS_PER_SOL,
     pubkey::Pubkey,
     signature::Keypair,
     signer::Signer,
-    system_program,
-    transaction::Transaction,
+    system_program, sysvar,
+    transaction::{Transaction, TransactionError},
 };
 
+/// Genesis hash of the base chain cluster, the test registry is deployed to
+pub const CLUSTER: Hash = Hash::new_from_array([1; 32]);
+
+/// Notice period for fee increases, configured in the test registry
+pub const FEE_NOTICE_SLOTS: u64 = 100;
+
+/// Account receiving slashed stakes in the test registry
+pub const TREASURY: Pubkey = Pubkey::new_from_array([7; 32]);
+
+/// Cooldown between the start and the completion of ER node exit in the test registry
+pub const UNBONDING_COOLDOWN_SECS: i64 = 3600;
+
 pub struct TestEnv {
+    pub context: ProgramTestContext,
     pub banks: BanksClient,
+    pub payer: Keypair,
+    pub admin: Keypair,
     pub identity: Keypair,
     pub record: ErRecord,
 }
 
-pub async fn setup() -> TestEnv {
+pub struct FleetEnv {
+    pub context: ProgramTestContext,
+    pub banks: BanksClient,
+    pub authority: Keypair,
+    pub identities: Vec<Keypair>,
+}
+
+fn program_test() -> ProgramTest {
     const PROGNAME: &str = "mdp";
     std::env::set_var("SBF_OUT_DIR", "target/deploy/");
-    let mut test = ProgramTest::new(PROGNAME, mdp::ID, None);
-    let identity = Keypair::new();
+    ProgramTest::new(PROGNAME, mdp::ID, None)
+}
 
+fn funded_keypair(test: &mut ProgramTest) -> Keypair {
+    let keypair = Keypair::new();
     test.add_account(
-        identity.pubkey(),
+        keypair.pubkey(),
         Account::new(LAMPORTS_PER_SOL, 0, &system_program::ID),
     );
+    keypair
+}
+
+/// Starts the test validator, with program configuration initialized by the
+/// returned admin, which is set up as the upgrade authority of the program
+async fn start(mut test: ProgramTest) -> (ProgramTestContext, Keypair) {
+    let admin = funded_keypair(&mut test);
+    test.add_account(
+        TREASURY,
+        Account::new(LAMPORTS_PER_SOL, 0, &system_program::ID),
+    );
+    // program is loaded as non upgradeable one, so inject its program data
+    // account, mimicking upgradeable deployment with admin as upgrade authority
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(admin.pubkey().as_ref());
+    test.add_account(
+        program_data_address(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data,
+            owner: bpf_loader_upgradeable::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let mut context = test.start_with_context().await;
+
+    let result = init_config(&mut context.banks_client, &admin, test_config(&admin)).await;
+    assert_ok!(result, "error processing init config transaction {}");
+    (context, admin)
+}
+
+pub async fn setup() -> TestEnv {
+    setup_with(program_test()).await
+}
+
+/// Same as [setup], but with the program loaded from the given build in `target/deploy/`,
+/// e.g. to compare the program built with different features
+pub async fn setup_build(name: &'static str) -> TestEnv {
+    std::env::set_var("SBF_OUT_DIR", "target/deploy/");
+    setup_with(ProgramTest::new(name, mdp::ID, None)).await
+}
+
+async fn setup_with(mut test: ProgramTest) -> TestEnv {
+    let identity = funded_keypair(&mut test);
+    let record = test_record(identity.pubkey(), identity.pubkey());
+    let (context, admin) = start(test).await;
+
+    TestEnv {
+        banks: context.banks_client.clone(),
+        payer: context.payer.insecure_clone(),
+        context,
+        admin,
+        identity,
+        record,
+    }
+}
+
+pub async fn setup_fleet(size: usize) -> FleetEnv {
+    let mut test = program_test();
+    let authority = funded_keypair(&mut test);
+    let identities = (0..size).map(|_| funded_keypair(&mut test)).collect();
+    let (context, _) = start(test).await;
+
+    FleetEnv {
+        banks: context.banks_client.clone(),
+        context,
+        authority,
+        identities,
+    }
+}
+
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[mdp::ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+thread_local! {
+    /// Compute units consumed by the last processed transaction, if measured
+    static COMPUTE_UNITS: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Enables measurement of compute units, consumed by transactions sent by the helpers
+/// on the current thread, each of them gets simulated before being processed
+pub fn measure_compute_units() {
+    COMPUTE_UNITS.set(Some(0));
+}
+
+/// Returns compute units consumed by the last transaction sent by the helpers,
+/// provided that measurement is enabled, see [measure_compute_units]
+pub fn last_compute_units() -> Option<u64> {
+    COMPUTE_UNITS.get()
+}
+
+/// Processes the transaction, measuring its compute units if enabled
+pub async fn process(banks: &mut BanksClient, tx: Transaction) -> Result<(), BanksClientError> {
+    if COMPUTE_UNITS.get().is_some() {
+        let simulation = banks.simulate_transaction(tx.clone()).await?;
+        let units = simulation.simulation_details.map(|d| d.units_consumed);
+        COMPUTE_UNITS.set(Some(units.unwrap_or_default()));
+    }
+    banks.process_transaction(tx).await
+}
+
+/// Error of the first instruction in transaction, failed with the given program error
+pub fn custom(err: MdpError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(err as u32))
+}
+
+/// Waits for the next blockhash, so that the transaction identical to an already
+/// processed one isn't deduplicated (returning the result of the earlier one)
+pub async fn refresh_blockhash(banks: &mut BanksClient) {
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    banks.get_new_latest_blockhash(&hash).await.unwrap();
+}
+
+/// Moves the clock of the test validator forward by the given number of seconds, the
+/// blockhash is refreshed, so that the transactions rejected before can be retried
+pub async fn advance_clock(context: &mut ProgramTestContext, secs: i64) {
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += secs;
+    context.set_sysvar(&clock);
+    refresh_blockhash(&mut context.banks_client).await;
+}
+
+/// Computes record's PDA for the given ER node identity
+pub fn record_pda(identity: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ER_RECORD_SEED, identity.as_ref()], &mdp::ID).0
+}
+
+pub fn test_config(admin: &Keypair) -> Config {
+    Config {
+        admin: admin.pubkey(),
+        cluster: CLUSTER,
+        fee_notice_slots: FEE_NOTICE_SLOTS,
+        arbiter: admin.pubkey(),
+        attesters: vec![admin.pubkey()],
+        treasury: TREASURY,
+        unbonding_cooldown_secs: UNBONDING_COOLDOWN_SECS,
+    }
+}
 
+pub fn test_record(identity: Pubkey, authority: Pubkey) -> ErRecord {
     let features = FeaturesSet::default().activate(Feature::Randomness);
-    let record = ErRecord::V0(RecordV0 {
-        identity: identity.pubkey(),
+    ErRecord::V1(RecordV1 {
+        identity,
+        authority,
+        cluster: CLUSTER,
+        chain_id: 1,
         status: ErStatus::Active,
         addr: "https://241.132.2.41:9324/".to_string(),
         block_time_ms: 50,
-        base_fee: 1000,
         features,
         load_average: 1_000_000,
+        metrics: LoadMetrics {
+            tps: 1_200,
+            latency_p50_ms: 20,
+            latency_p99_ms: 90,
+            active_delegations: 100,
+            max_delegations: 10_000,
+            memory_pressure_bps: 2_000,
+        },
         country_code: CountryCode::from("083"),
-    });
-    let (banks, _, _) = test.start().await;
-
-    TestEnv {
-        banks,
-        identity,
-        record,
-    }
+        software_version: SemVer::new(0, 1, 0),
+        protocol_version: SemVer::new(1, 0, 0),
+        sequence: 0,
+        // set by the program on registration
+        registered_slot: u64::MAX,
+        heartbeats: Default::default(),
+        fees: FeeSchedule {
+            lamports_per_signature: 1000,
+            micro_lamports_per_cu: 100,
+            surcharges: vec![FeatureSurcharge {
+                feature: Feature::Randomness,
+                lamports: 5000,
+            }],
+            priority_multiplier_bps: Some(20_000),
+        },
+        pending_fees: None,
+        exit_after: None,
+        tee: None,
+        tls_fingerprint: None,
+        region: Some(Region::EuropeCentral),
+        location: Some(GeoPoint::from_degrees(50.1109, 8.6821)),
+        maintenance: vec![],
+    })
 }
 
 pub async fn register(
@@ -62,76 +286,513 @@ pub async fn register(
     identity: &Keypair,
 ) -> Result<(), BanksClientError> {
     let pda = record.pda().0;
+    let pages = listed_pages(&record);
     let ix = Instruction::Register(record);
+    let mut accounts = vec![
+        AccountMeta::new(identity.pubkey(), true),
+        AccountMeta::new(pda, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(Config::pda().0, false),
+    ];
+    accounts.extend(index_metas(pages));
+    let ix = SolanaInstruction::new_with_borsh(mdp::ID, &ix, accounts);
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&identity.pubkey()), &[identity], hash);
+    process(banks, tx).await
+}
+
+pub async fn begin_unregister(
+    banks: &mut BanksClient,
+    identity: &Keypair,
+    pda: Pubkey,
+) -> Result<(), BanksClientError> {
+    let pages = load_record(banks, pda)
+        .await
+        .map(|r| listed_pages(&r))
+        .unwrap_or_default();
+    let ix = Instruction::BeginUnregister(identity.pubkey());
+    let mut accounts = vec![
+        AccountMeta::new(identity.pubkey(), true),
+        AccountMeta::new(pda, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(Config::pda().0, false),
+    ];
+    accounts.extend(index_metas(pages));
+    let ix = SolanaInstruction::new_with_borsh(mdp::ID, &ix, accounts);
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&identity.pubkey()), &[&identity], hash);
+    process(banks, tx).await
+}
+
+pub async fn complete_unregister(
+    banks: &mut BanksClient,
+    identity: &Keypair,
+    pda: Pubkey,
+) -> Result<(), BanksClientError> {
+    complete_unregister_to(banks, identity, pda, None).await
+}
+
+pub async fn complete_unregister_to(
+    banks: &mut BanksClient,
+    identity: &Keypair,
+    pda: Pubkey,
+    recipient: Option<Pubkey>,
+) -> Result<(), BanksClientError> {
+    let ix = Instruction::CompleteUnregister(identity.pubkey());
+    let mut accounts = vec![
+        AccountMeta::new(identity.pubkey(), true),
+        AccountMeta::new(pda, false),
+        AccountMeta::new(BondRecord::pda(&identity.pubkey()).0, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    if let Some(recipient) = recipient {
+        accounts.push(AccountMeta::new(recipient, false));
+    }
+    let ix = SolanaInstruction::new_with_borsh(mdp::ID, &ix, accounts);
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&identity.pubkey()), &[&identity], hash);
+    process(banks, tx).await
+}
+
+/// Fetches ER record stored at the given PDA, if it exists
+pub async fn load_record(banks: &mut BanksClient, pda: Pubkey) -> Option<ErRecord> {
+    let account = banks.get_account(pda).await.unwrap()?;
+    ErRecord::try_from_slice(&account.data).ok()
+}
+
+/// Sync updating only the status of the ER node
+pub fn status_update(identity: Pubkey, sequence: u64, status: ErStatus) -> SyncInstruction {
+    SyncInstruction::V1(SyncRecordV1 {
+        identity,
+        sequence,
+        status: Some(status),
+        block_time_ms: None,
+        features: None,
+        load_average: None,
+        metrics: None,
+        country_code: None,
+        region: None,
+        location: None,
+        software_version: None,
+        protocol_version: None,
+        fees: None,
+        addr: None,
+        tls_fingerprint: None,
+        maintenance: None,
+    })
+}
+
+/// Returns index pages touched by syncing the given record over the one stored on chain
+async fn sync_pages(banks: &mut BanksClient, record: &ErRecord) -> Vec<Pubkey> {
+    let current = load_record(banks, record.pda().0).await;
+    current.map_or_else(Vec::new, |c| touched_pages(&c, record))
+}
+
+fn index_metas(pages: Vec<Pubkey>) -> impl Iterator<Item = AccountMeta> {
+    pages.into_iter().map(|p| AccountMeta::new(p, false))
+}
+
+fn sync_instruction(record: &ErRecord) -> SyncInstruction {
+    SyncInstruction::V1(SyncRecordV1 {
+        identity: *record.identity(),
+        sequence: record.sequence(),
+        status: Some(record.status()),
+        addr: Some(record.addr().to_owned()),
+        tls_fingerprint: Some(record.tls_fingerprint().copied()),
+        block_time_ms: Some(record.block_time_ms()),
+        features: Some(record.features().clone()),
+        load_average: Some(record.load_average()),
+        metrics: Some(*record.metrics()),
+        country_code: Some(record.country_code()),
+        region: Some(record.region()),
+        location: Some(record.location().copied()),
+        software_version: Some(record.software_version()),
+        protocol_version: Some(record.protocol_version()),
+        // re-announce the latest schedule, so that pending increase stays in place
+        fees: Some(
+            record
+                .pending_fees()
+                .map_or(record.fees(), |p| &p.fees)
+                .clone(),
+        ),
+        maintenance: Some(record.maintenance().to_vec()),
+    })
+}
+
+pub async fn sync(
+    banks: &mut BanksClient,
+    identity: &Keypair,
+    record: ErRecord,
+) -> Result<(), BanksClientError> {
+    let pages = sync_pages(banks, &record).await;
+    sync_with_pages(banks, identity, record, pages).await
+}
+
+/// Syncs the record passing the given index pages, instead of the ones touched by the sync
+pub async fn sync_with_pages(
+    banks: &mut BanksClient,
+    identity: &Keypair,
+    record: ErRecord,
+    pages: Vec<Pubkey>,
+) -> Result<(), BanksClientError> {
+    let pda = record.pda().0;
+    let ix = Instruction::Sync(sync_instruction(&record));
+    let mut accounts = vec![
+        AccountMeta::new(identity.pubkey(), true),
+        AccountMeta::new(pda, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(Config::pda().0, false),
+    ];
+    accounts.extend(index_metas(pages));
+    let ix = SolanaInstruction::new_with_borsh(mdp::ID, &ix, accounts);
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&identity.pubkey()), &[&identity], hash);
+    process(banks, tx).await
+}
+
+pub async fn relayed_sync(
+    banks: &mut BanksClient,
+    relayer: &Keypair,
+    identity: &Keypair,
+    record: ErRecord,
+) -> Result<(), BanksClientError> {
+    let pda = record.pda().0;
+    let pages = sync_pages(banks, &record).await;
+    let relayed = RelayedSync {
+        sync: sync_instruction(&record),
+    };
+    let signature = identity.sign_message(&relayed.message());
+    let verify_ix = relayed.verify_instruction(&signature.into());
+    let ix = Instruction::RelayedSync(relayed);
+    let mut accounts = vec![
+        AccountMeta::new(relayer.pubkey(), true),
+        AccountMeta::new(pda, false),
+        AccountMeta::new_readonly(sysvar::instructions::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(Config::pda().0, false),
+    ];
+    accounts.extend(index_metas(pages));
+    let ix = SolanaInstruction::new_with_borsh(mdp::ID, &ix, accounts);
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[verify_ix, ix],
+        Some(&relayer.pubkey()),
+        &[relayer],
+        hash,
+    );
+    process(banks, tx).await
+}
+
+pub fn batch_sync_instruction(
+    authority: &Pubkey,
+    batch: Vec<SyncInstruction>,
+) -> SolanaInstruction {
+    let mut accounts = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(Config::pda().0, false),
+    ];
+    accounts.extend(batch.iter().map(|ix| AccountMeta::new(ix.pda(), false)));
+    let ix = Instruction::BatchSync(batch);
+    SolanaInstruction::new_with_borsh(mdp::ID, &ix, accounts)
+}
+
+pub fn batch_sync_transaction(
+    authority: &Keypair,
+    batch: Vec<SyncInstruction>,
+    hash: Hash,
+) -> Transaction {
+    let ix = batch_sync_instruction(&authority.pubkey(), batch);
+    Transaction::new_signed_with_payer(&[ix], Some(&authority.pubkey()), &[authority], hash)
+}
+
+pub async fn claim_name(
+    banks: &mut BanksClient,
+    owner: &Keypair,
+    name: &str,
+    parent_owner: Option<&Keypair>,
+    identity: Option<&Keypair>,
+) -> Result<(), BanksClientError> {
+    let ix = Instruction::ClaimName(name.to_string(), identity.map(|id| id.pubkey()));
+    let mut accounts = vec![
+        AccountMeta::new(owner.pubkey(), true),
+        AccountMeta::new(NameRecord::pda(name).0, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    let mut signers = vec![owner];
+    if let Some(parent_owner) = parent_owner {
+        let parent = parent_name(name).unwrap();
+        accounts.push(AccountMeta::new(NameRecord::pda(parent).0, false));
+        accounts.push(AccountMeta::new_readonly(parent_owner.pubkey(), true));
+        signers.push(parent_owner);
+    }
+    if let Some(identity) = identity {
+        let record = test_record(identity.pubkey(), identity.pubkey());
+        accounts.push(AccountMeta::new_readonly(identity.pubkey(), true));
+        accounts.push(AccountMeta::new(
+            ReverseNameRecord::pda(&identity.pubkey()).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(record.pda().0, false));
+        signers.push(identity);
+    }
+    signers.sort_by_key(|s| s.pubkey());
+    signers.dedup_by_key(|s| s.pubkey());
+    let ix = SolanaInstruction::new_with_borsh(mdp::ID, &ix, accounts);
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&owner.pubkey()), &signers, hash);
+    process(banks, tx).await
+}
+
+pub async fn release_name(
+    banks: &mut BanksClient,
+    owner: &Keypair,
+    name: &str,
+    identity: Option<Pubkey>,
+) -> Result<(), BanksClientError> {
+    let ix = Instruction::ReleaseName(name.to_string());
+    let mut accounts = vec![
+        AccountMeta::new(owner.pubkey(), true),
+        AccountMeta::new(NameRecord::pda(name).0, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    if let Some(parent) = parent_name(name) {
+        accounts.push(AccountMeta::new(NameRecord::pda(parent).0, false));
+    }
+    if let Some(identity) = identity {
+        accounts.push(AccountMeta::new(ReverseNameRecord::pda(&identity).0, false));
+    }
+    let ix = SolanaInstruction::new_with_borsh(mdp::ID, &ix, accounts);
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&owner.pubkey()), &[owner], hash);
+    process(banks, tx).await
+}
+
+pub async fn transfer_name(
+    banks: &mut BanksClient,
+    owner: &Keypair,
+    name: &str,
+    new_owner: Pubkey,
+) -> Result<(), BanksClientError> {
+    let ix = Instruction::TransferName(name.to_string(), new_owner);
+    let ix = SolanaInstruction::new_with_borsh(
+        mdp::ID,
+        &ix,
+        vec![
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(NameRecord::pda(name).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&owner.pubkey()), &[owner], hash);
+    process(banks, tx).await
+}
+
+pub async fn attest(
+    banks: &mut BanksClient,
+    attester: &Keypair,
+    observation: Observation,
+) -> Result<(), BanksClientError> {
+    let identity = observation.identity;
+    let record_pda = record_pda(&identity);
+    let ix = Instruction::Attest(observation);
+    let ix = SolanaInstruction::new_with_borsh(
+        mdp::ID,
+        &ix,
+        vec![
+            AccountMeta::new(attester.pubkey(), true),
+            AccountMeta::new(AttestationRecord::pda(&identity).0, false),
+            AccountMeta::new_readonly(record_pda, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(Config::pda().0, false),
+        ],
+    );
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&attester.pubkey()), &[attester], hash);
+    process(banks, tx).await
+}
+
+pub async fn update_reputation(
+    banks: &mut BanksClient,
+    payer: &Keypair,
+    identity: Pubkey,
+) -> Result<(), BanksClientError> {
+    let record_pda = record_pda(&identity);
+    let ix = Instruction::UpdateReputation(identity);
+    let ix = SolanaInstruction::new_with_borsh(
+        mdp::ID,
+        &ix,
+        vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(ReputationRecord::pda(&identity).0, false),
+            AccountMeta::new_readonly(record_pda, false),
+            AccountMeta::new_readonly(AttestationRecord::pda(&identity).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], hash);
+    process(banks, tx).await
+}
+
+pub async fn bond(
+    banks: &mut BanksClient,
+    identity: &Keypair,
+    lamports: u64,
+) -> Result<(), BanksClientError> {
+    let record_pda = record_pda(&identity.pubkey());
+    let ix = Instruction::Bond(lamports);
     let ix = SolanaInstruction::new_with_borsh(
         mdp::ID,
         &ix,
         vec![
             AccountMeta::new(identity.pubkey(), true),
-            AccountMeta::new(pda, false),
+            AccountMeta::new(BondRecord::pda(&identity.pubkey()).0, false),
+            AccountMeta::new_readonly(record_pda, false),
             AccountMeta::new_readonly(system_program::ID, false),
         ],
     );
     let hash = banks.get_latest_blockhash().await.unwrap();
     let tx = Transaction::new_signed_with_payer(&[ix], Some(&identity.pubkey()), &[identity], hash);
-    banks.process_transaction(tx).await
+    process(banks, tx).await
+}
+
+pub async fn open_dispute(
+    banks: &mut BanksClient,
+    challenger: &Keypair,
+    claim: DisputeClaim,
+) -> Result<(), BanksClientError> {
+    let dispute_pda = DisputeRecord::pda(&claim.identity, &challenger.pubkey()).0;
+    let bond_pda = BondRecord::pda(&claim.identity).0;
+    let ix = Instruction::OpenDispute(claim);
+    let ix = SolanaInstruction::new_with_borsh(
+        mdp::ID,
+        &ix,
+        vec![
+            AccountMeta::new(challenger.pubkey(), true),
+            AccountMeta::new(dispute_pda, false),
+            AccountMeta::new(bond_pda, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&challenger.pubkey()), &[challenger], hash);
+    process(banks, tx).await
 }
 
-pub async fn unregister(
+pub async fn resolve_dispute(
+    banks: &mut BanksClient,
+    resolver: &Keypair,
+    identity: Pubkey,
+    challenger: Pubkey,
+    ruling: Ruling,
+) -> Result<(), BanksClientError> {
+    let ix = Instruction::ResolveDispute(ruling);
+    let ix = SolanaInstruction::new_with_borsh(
+        mdp::ID,
+        &ix,
+        vec![
+            AccountMeta::new(resolver.pubkey(), true),
+            AccountMeta::new_readonly(Config::pda().0, false),
+            AccountMeta::new(DisputeRecord::pda(&identity, &challenger).0, false),
+            AccountMeta::new(BondRecord::pda(&identity).0, false),
+            AccountMeta::new(challenger, false),
+            AccountMeta::new(TREASURY, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&resolver.pubkey()), &[resolver], hash);
+    process(banks, tx).await
+}
+
+pub async fn write_tee_quote(
     banks: &mut BanksClient,
     identity: &Keypair,
-    pda: Pubkey,
+    chunk: QuoteChunk,
 ) -> Result<(), BanksClientError> {
-    let ix = Instruction::Unregister(identity.pubkey());
+    let ix = Instruction::WriteTeeQuote(chunk);
     let ix = SolanaInstruction::new_with_borsh(
         mdp::ID,
         &ix,
         vec![
             AccountMeta::new(identity.pubkey(), true),
-            AccountMeta::new(pda, false),
+            AccountMeta::new(TeeQuoteRecord::pda(&identity.pubkey()).0, false),
             AccountMeta::new_readonly(system_program::ID, false),
         ],
     );
     let hash = banks.get_latest_blockhash().await.unwrap();
-    let tx =
-        Transaction::new_signed_with_payer(&[ix], Some(&identity.pubkey()), &[&identity], hash);
-    banks.process_transaction(tx).await
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&identity.pubkey()), &[identity], hash);
+    process(banks, tx).await
 }
 
-pub async fn sync(
+pub async fn publish_tee_quote(
     banks: &mut BanksClient,
     identity: &Keypair,
-    record: ErRecord,
+    publication: QuotePublication,
 ) -> Result<(), BanksClientError> {
-    let pda = record.pda().0;
-    let ix = Instruction::Sync(SyncInstruction::V0(SyncRecordV0 {
-        identity: *record.identity(),
-        status: Some(record.status()),
-        addr: Some(record.addr().to_owned()),
-        block_time_ms: Some(record.block_time_ms()),
-        base_fee: Some(record.base_fee()),
-        features: Some(record.features().clone()),
-        load_average: Some(record.load_average()),
-        country_code: Some(record.country_code()),
-    }));
+    let key = IndexKey::Feature(Feature::TeeAttestation);
+    let tee_index_page = IndexPage::pda(&key, IndexPage::of(&identity.pubkey())).0;
+    let ix = Instruction::PublishTeeQuote(publication);
     let ix = SolanaInstruction::new_with_borsh(
         mdp::ID,
         &ix,
         vec![
             AccountMeta::new(identity.pubkey(), true),
-            AccountMeta::new(pda, false),
+            AccountMeta::new(record_pda(&identity.pubkey()), false),
+            AccountMeta::new_readonly(TeeQuoteRecord::pda(&identity.pubkey()).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            // the node gets listed in the index of TEE attested ones
+            AccountMeta::new(tee_index_page, false),
         ],
     );
     let hash = banks.get_latest_blockhash().await.unwrap();
-    let tx =
-        Transaction::new_signed_with_payer(&[ix], Some(&identity.pubkey()), &[&identity], hash);
-    banks.process_transaction(tx).await
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&identity.pubkey()), &[identity], hash);
+    process(banks, tx).await
 }
 
-#[macro_export]
-macro_rules! assert_ok {
-    ($result: ident, $errmsg: expr) => {{
-        assert!($result.is_ok(), $errmsg, $result.unwrap_err());
-        $result.unwrap()
-    }};
+pub async fn init_config(
+    banks: &mut BanksClient,
+    payer: &Keypair,
+    config: Config,
+) -> Result<(), BanksClientError> {
+    let ix = Instruction::InitConfig(config);
+    let ix = SolanaInstruction::new_with_borsh(
+        mdp::ID,
+        &ix,
+        vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(Config::pda().0, false),
+            AccountMeta::new_readonly(program_data_address(), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], hash);
+    process(banks, tx).await
+}
+
+pub async fn update_config(
+    banks: &mut BanksClient,
+    admin: &Keypair,
+    config: Config,
+) -> Result<(), BanksClientError> {
+    let ix = Instruction::UpdateConfig(config);
+    let ix = SolanaInstruction::new_with_borsh(
+        mdp::ID,
+        &ix,
+        vec![
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new(Config::pda().0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    );
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&admin.pubkey()), &[admin], hash);
+    process(banks, tx).await
 }
@@ -0,0 +1,109 @@
+use borsh::BorshDeserialize;
+use common::TestEnv;
+use mdp::{
+    error::MdpError,
+    state::attestation::{AttestationRecord, Observation},
+};
+use sdk::{
+    clock::Clock, instruction::InstructionError, signer::Signer, transaction::TransactionError,
+};
+
+pub mod common;
+
+#[tokio::test]
+async fn test_attestation() {
+    let TestEnv {
+        mut banks,
+        payer,
+        admin,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    // admin vets another client as attester
+    let mut config = common::test_config(&admin);
+    config.attesters.push(payer.pubkey());
+    let result = common::update_config(&mut banks, &admin, config).await;
+    assert_ok!(result, "error processing update config transaction {}");
+
+    let result = banks.get_sysvar::<Clock>().await;
+    let slot = assert_ok!(result, "error querying clock sysvar {}").slot;
+    let observations = [(&admin, 40, true), (&payer, 60, true), (&payer, 0, false)];
+    for (attester, latency_ms, success) in observations {
+        let observation = Observation {
+            identity: identity.pubkey(),
+            slot,
+            latency_ms,
+            success,
+        };
+        let result = common::attest(&mut banks, attester, observation).await;
+        assert_ok!(result, "error processing attest transaction {}");
+    }
+
+    let pda = AttestationRecord::pda(&identity.pubkey()).0;
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying attestation PDA from banks {}");
+    let result = AttestationRecord::try_from_slice(&acc.unwrap().data);
+    let attestation = assert_ok!(result, "error deserializing attestation record {}");
+    let stats = attestation.stats(slot);
+    assert_eq!(stats.successes, 2);
+    assert_eq!(stats.failures, 1);
+    assert_eq!(stats.avg_latency_ms(), Some(50));
+}
+
+#[tokio::test]
+async fn test_attestation_rejected() {
+    let TestEnv {
+        mut banks,
+        payer,
+        admin,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let result = banks.get_sysvar::<Clock>().await;
+    let slot = assert_ok!(result, "error querying clock sysvar {}").slot;
+    let observation = Observation {
+        identity: identity.pubkey(),
+        slot,
+        latency_ms: 10,
+        success: true,
+    };
+    let expected = TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(MdpError::InvalidObservation as u32),
+    );
+
+    // ER node cannot vouch for itself
+    let result = common::attest(&mut banks, &identity, observation).await;
+    let err = result.expect_err("self attestation should be rejected");
+    assert_eq!(err.unwrap(), expected);
+
+    // only attesters allowed in program config can attest
+    let result = common::attest(&mut banks, &payer, observation).await;
+    let err = result.expect_err("attestation by unknown key should be rejected");
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MdpError::Unauthorized as u32)
+        )
+    );
+
+    // observations from the future are rejected
+    let future = Observation {
+        slot: slot + 1_000,
+        ..observation
+    };
+    let result = common::attest(&mut banks, &admin, future).await;
+    let err = result.expect_err("future observation should be rejected");
+    assert_eq!(err.unwrap(), expected);
+}
@@ -0,0 +1,115 @@
+use common::FleetEnv;
+use mdp::{consts::MAX_BATCH_SYNC, error::MdpError, state::status::ErStatus};
+use sdk::{packet::PACKET_DATA_SIZE, signer::Signer};
+
+pub mod common;
+
+/// Upper bound of compute units a single status update in a batch is allowed to consume
+const UPDATE_CU_BUDGET: u64 = 20_000;
+
+async fn register_fleet(env: &mut FleetEnv) {
+    for identity in &env.identities {
+        let record = common::test_record(identity.pubkey(), env.authority.pubkey());
+        let result = common::register(&mut env.banks, record, identity).await;
+        assert_ok!(result, "error processing register transaction {}");
+    }
+}
+
+#[tokio::test]
+async fn test_batch_sync() {
+    let mut env = common::setup_fleet(3).await;
+    register_fleet(&mut env).await;
+
+    let batch = env
+        .identities
+        .iter()
+        .map(|id| common::status_update(id.pubkey(), 1, ErStatus::Offline))
+        .collect();
+    let hash = env.banks.get_latest_blockhash().await.unwrap();
+    let tx = common::batch_sync_transaction(&env.authority, batch, hash);
+    let result = env.banks.process_transaction(tx).await;
+    assert_ok!(result, "error processing batch sync transaction {}");
+
+    for identity in &env.identities {
+        let pda = common::record_pda(&identity.pubkey());
+        let record = common::load_record(&mut env.banks, pda).await.unwrap();
+        assert_eq!(record.status(), ErStatus::Offline);
+        assert_eq!(record.sequence(), 1);
+    }
+}
+
+#[tokio::test]
+async fn test_batch_sync_is_atomic() {
+    let mut env = common::setup_fleet(2).await;
+    register_fleet(&mut env).await;
+
+    // the second update is stale, so the first one shouldn't be applied either
+    let batch = vec![
+        common::status_update(env.identities[0].pubkey(), 1, ErStatus::Offline),
+        common::status_update(env.identities[1].pubkey(), 0, ErStatus::Offline),
+    ];
+    let hash = env.banks.get_latest_blockhash().await.unwrap();
+    let tx = common::batch_sync_transaction(&env.authority, batch, hash);
+    let result = env.banks.process_transaction(tx).await;
+    let err = result
+        .expect_err("batch with stale update should be rejected")
+        .unwrap();
+    assert_eq!(err, common::custom(MdpError::StaleSequence));
+
+    for identity in &env.identities {
+        let pda = common::record_pda(&identity.pubkey());
+        let record = common::load_record(&mut env.banks, pda).await.unwrap();
+        assert_eq!(record.status(), ErStatus::Active);
+        assert_eq!(record.sequence(), 0);
+    }
+}
+
+#[tokio::test]
+async fn test_batch_sync_wrong_authority() {
+    let mut env = common::setup_fleet(1).await;
+    register_fleet(&mut env).await;
+
+    // ER node identity is not the authority of its own record
+    let identity = &env.identities[0];
+    let batch = vec![common::status_update(
+        identity.pubkey(),
+        1,
+        ErStatus::Offline,
+    )];
+    let hash = env.banks.get_latest_blockhash().await.unwrap();
+    let tx = common::batch_sync_transaction(identity, batch, hash);
+    let result = env.banks.process_transaction(tx).await;
+    let err = result
+        .expect_err("batch signed by non authority should fail")
+        .unwrap();
+    assert_eq!(err, common::custom(MdpError::Unauthorized));
+}
+
+#[tokio::test]
+async fn test_batch_sync_compute_units() {
+    let mut env = common::setup_fleet(MAX_BATCH_SYNC).await;
+    register_fleet(&mut env).await;
+
+    let batch = env
+        .identities
+        .iter()
+        .map(|id| common::status_update(id.pubkey(), 1, ErStatus::Draining))
+        .collect();
+    let hash = env.banks.get_latest_blockhash().await.unwrap();
+    let tx = common::batch_sync_transaction(&env.authority, batch, hash);
+
+    let size = tx.message_data().len() + 1 + tx.signatures.len() * 64;
+    assert!(
+        size <= PACKET_DATA_SIZE,
+        "batch of {MAX_BATCH_SYNC} updates doesn't fit into transaction: {size} bytes"
+    );
+
+    let result = env.banks.simulate_transaction(tx).await;
+    let simulation = assert_ok!(result, "error simulating batch sync transaction {}");
+    assert!(matches!(simulation.result, Some(Ok(()))));
+    let units = simulation.simulation_details.unwrap().units_consumed;
+    assert!(
+        units <= UPDATE_CU_BUDGET * MAX_BATCH_SYNC as u64,
+        "batch sync consumed {units} CUs, budget is {UPDATE_CU_BUDGET} per update"
+    );
+}
@@ -0,0 +1,131 @@
+use borsh::BorshDeserialize;
+use common::TestEnv;
+use mdp::{
+    error::MdpError,
+    state::{config::Config, record::ErRecord},
+};
+use sdk::{
+    account::{Account, AccountSharedData},
+    hash::Hash,
+    instruction::InstructionError,
+    rent::Rent,
+    signer::Signer,
+    transaction::TransactionError,
+};
+
+pub mod common;
+
+#[tokio::test]
+async fn test_config_initialized_once() {
+    let TestEnv {
+        mut banks,
+        admin,
+        // keeps producing new blockhashes
+        context: _context,
+        ..
+    } = common::setup().await;
+
+    let result = banks.get_account(Config::pda().0).await;
+    let acc = assert_ok!(result, "error querying config PDA from banks {}");
+    let result = Config::try_from_slice(&acc.unwrap().data);
+    let config = assert_ok!(result, "error deserializing config {}");
+    assert_eq!(config.admin, admin.pubkey());
+    assert_eq!(config.cluster, common::CLUSTER);
+
+    // the same transaction has already been processed on setup
+    common::refresh_blockhash(&mut banks).await;
+    let config = common::test_config(&admin);
+    let result = common::init_config(&mut banks, &admin, config).await;
+    let err = result
+        .expect_err("config should only be initialized once")
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn test_config_update() {
+    let TestEnv {
+        mut banks,
+        admin,
+        identity,
+        ..
+    } = common::setup().await;
+
+    let mut config = common::test_config(&admin);
+    config.cluster = Hash::new_from_array([2; 32]);
+
+    let result = common::update_config(&mut banks, &identity, config.clone()).await;
+    assert!(
+        result.is_err(),
+        "non admin should not be able to update config"
+    );
+
+    let result = common::update_config(&mut banks, &admin, config).await;
+    assert_ok!(result, "error processing update config transaction {}");
+
+    let result = banks.get_account(Config::pda().0).await;
+    let acc = assert_ok!(result, "error querying config PDA from banks {}");
+    let result = Config::try_from_slice(&acc.unwrap().data);
+    let config = assert_ok!(result, "error deserializing config {}");
+    assert_eq!(config.cluster, Hash::new_from_array([2; 32]));
+}
+
+#[tokio::test]
+async fn test_register_cluster_mismatch() {
+    let TestEnv {
+        mut banks,
+        identity,
+        ..
+    } = common::setup().await;
+
+    let ErRecord::V1(mut record) = common::test_record(identity.pubkey(), identity.pubkey());
+    record.cluster = Hash::new_from_array([3; 32]);
+
+    let result = common::register(&mut banks, ErRecord::V1(record), &identity).await;
+    let err = result
+        .expect_err("record for foreign cluster should be rejected")
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MdpError::ClusterMismatch as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_config_migration() {
+    let TestEnv {
+        mut context,
+        mut banks,
+        admin,
+        ..
+    } = common::setup().await;
+
+    // config written by earlier program version, lacking the fields added since
+    let pda = Config::pda().0;
+    let mut data = admin.pubkey().to_bytes().to_vec();
+    data.extend_from_slice(common::CLUSTER.as_ref());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    let account = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: mdp::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    context.set_account(&pda, &AccountSharedData::from(account));
+
+    let config = common::test_config(&admin);
+    let result = common::update_config(&mut banks, &admin, config.clone()).await;
+    assert_ok!(result, "error processing update config transaction {}");
+
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying config PDA from banks {}");
+    let result = Config::try_from_slice(&acc.unwrap().data);
+    assert_eq!(assert_ok!(result, "error deserializing config {}"), config);
+}
@@ -0,0 +1,207 @@
+use borsh::BorshDeserialize;
+use common::{TestEnv, TREASURY};
+use mdp::{
+    error::MdpError,
+    state::{
+        bond::BondRecord,
+        dispute::{
+            DisputeClaim, DisputeReason, DisputeRecord, DisputeState, Ruling, SlashRecipient,
+        },
+    },
+};
+use program_test::BanksClient;
+use sdk::{
+    hash::Hash, instruction::InstructionError, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey,
+    signer::Signer, transaction::TransactionError,
+};
+
+pub mod common;
+
+const BOND: u64 = LAMPORTS_PER_SOL / 2;
+const STAKE: u64 = LAMPORTS_PER_SOL / 10;
+
+async fn fetch<T: BorshDeserialize + std::fmt::Debug>(banks: &mut BanksClient, pda: Pubkey) -> T {
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying PDA from banks {}");
+    let result = T::try_from_slice(&acc.unwrap().data);
+    assert_ok!(result, "error deserializing PDA {}")
+}
+
+async fn balance(banks: &mut BanksClient, pubkey: Pubkey) -> u64 {
+    banks.get_balance(pubkey).await.unwrap()
+}
+
+fn claim(identity: Pubkey, stake: u64) -> DisputeClaim {
+    DisputeClaim {
+        identity,
+        reason: DisputeReason::WrongEndpoint,
+        evidence: Hash::new_from_array([9; 32]),
+        stake,
+    }
+}
+
+/// Registers and bonds ER node, then opens dispute against it on behalf of the payer
+async fn setup_dispute() -> TestEnv {
+    let mut env = common::setup().await;
+    let record = common::test_record(env.identity.pubkey(), env.identity.pubkey());
+    let result = common::register(&mut env.banks, record, &env.identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let result = common::bond(&mut env.banks, &env.identity, BOND).await;
+    assert_ok!(result, "error processing bond transaction {}");
+
+    let claim = claim(env.identity.pubkey(), STAKE);
+    let result = common::open_dispute(&mut env.banks, &env.payer, claim).await;
+    assert_ok!(result, "error processing open dispute transaction {}");
+    env
+}
+
+#[tokio::test]
+async fn test_dispute_upheld() {
+    let TestEnv {
+        mut banks,
+        payer,
+        admin,
+        identity,
+        context: _context,
+        ..
+    } = setup_dispute().await;
+    let bond_pda = BondRecord::pda(&identity.pubkey()).0;
+    let dispute_pda = DisputeRecord::pda(&identity.pubkey(), &payer.pubkey()).0;
+
+    let bond: BondRecord = fetch(&mut banks, bond_pda).await;
+    assert_eq!(bond.amount, BOND);
+    assert_eq!(bond.locked, STAKE);
+    assert_eq!(bond.open_disputes, 1);
+
+    // only admin or arbiter can resolve
+    let ruling = Ruling {
+        upheld: true,
+        slash_to: SlashRecipient::Winner,
+    };
+    let result = common::resolve_dispute(
+        &mut banks,
+        &identity,
+        identity.pubkey(),
+        payer.pubkey(),
+        ruling,
+    )
+    .await;
+    assert!(
+        result.is_err(),
+        "ER node should not resolve its own dispute"
+    );
+
+    let dispute: DisputeRecord = fetch(&mut banks, dispute_pda).await;
+    assert_eq!(dispute.state, DisputeState::Open);
+    let rent = balance(&mut banks, dispute_pda).await - STAKE;
+    let before = balance(&mut banks, payer.pubkey()).await;
+    let result = common::resolve_dispute(
+        &mut banks,
+        &admin,
+        identity.pubkey(),
+        payer.pubkey(),
+        ruling,
+    )
+    .await;
+    assert_ok!(result, "error processing resolve dispute transaction {}");
+
+    // challenger gets both the counter bond and the slashed stake, as well as the rent
+    let after = balance(&mut banks, payer.pubkey()).await;
+    assert_eq!(after - before, 2 * STAKE + rent);
+    let bond: BondRecord = fetch(&mut banks, bond_pda).await;
+    assert_eq!(bond.amount, BOND - STAKE);
+    assert_eq!(bond.locked, 0);
+    assert_eq!(bond.open_disputes, 0);
+    let result = banks.get_account(dispute_pda).await;
+    let acc = assert_ok!(result, "error querying dispute PDA from banks {}");
+    assert!(acc.is_none(), "resolved dispute should be closed");
+
+    // resolution is final
+    common::refresh_blockhash(&mut banks).await;
+    let result = common::resolve_dispute(
+        &mut banks,
+        &admin,
+        identity.pubkey(),
+        payer.pubkey(),
+        ruling,
+    )
+    .await;
+    let err = result
+        .expect_err("dispute should be resolved once")
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::UninitializedAccount)
+    );
+
+    // the same challenger can dispute the node again
+    let claim = claim(identity.pubkey(), STAKE / 2);
+    let result = common::open_dispute(&mut banks, &payer, claim).await;
+    assert_ok!(result, "error processing open dispute transaction {}");
+    let bond: BondRecord = fetch(&mut banks, bond_pda).await;
+    assert_eq!(bond.locked, STAKE / 2);
+    assert_eq!(bond.open_disputes, 1);
+}
+
+#[tokio::test]
+async fn test_dispute_rejected() {
+    let TestEnv {
+        mut banks,
+        payer,
+        admin,
+        identity,
+        ..
+    } = setup_dispute().await;
+    let bond_pda = BondRecord::pda(&identity.pubkey()).0;
+
+    let before = balance(&mut banks, TREASURY).await;
+    let ruling = Ruling {
+        upheld: false,
+        slash_to: SlashRecipient::Treasury,
+    };
+    let result = common::resolve_dispute(
+        &mut banks,
+        &admin,
+        identity.pubkey(),
+        payer.pubkey(),
+        ruling,
+    )
+    .await;
+    assert_ok!(result, "error processing resolve dispute transaction {}");
+
+    // counter bond is slashed to the treasury, while ER node's bond is unlocked
+    let after = balance(&mut banks, TREASURY).await;
+    assert_eq!(after - before, STAKE);
+    let bond: BondRecord = fetch(&mut banks, bond_pda).await;
+    assert_eq!(bond.amount, BOND);
+    assert_eq!(bond.locked, 0);
+    let dispute_pda = DisputeRecord::pda(&identity.pubkey(), &payer.pubkey()).0;
+    let result = banks.get_account(dispute_pda).await;
+    let acc = assert_ok!(result, "error querying dispute PDA from banks {}");
+    assert!(acc.is_none(), "resolved dispute should be closed");
+}
+
+#[tokio::test]
+async fn test_dispute_insufficient_bond() {
+    let TestEnv {
+        mut banks,
+        admin,
+        identity,
+        ..
+    } = setup_dispute().await;
+
+    // the rest of the bond can be claimed by others, but not more than that
+    let claim = claim(identity.pubkey(), BOND - STAKE + 1);
+    let result = common::open_dispute(&mut banks, &admin, claim).await;
+    let err = result
+        .expect_err("dispute exceeding available bond should be rejected")
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MdpError::InsufficientBond as u32)
+        )
+    );
+}
@@ -0,0 +1,416 @@
+use common::{TestEnv, TREASURY, UNBONDING_COOLDOWN_SECS};
+use mdp::{
+    consts::MAX_ATTESTERS,
+    error::MdpError,
+    instructions::Instruction,
+    state::{
+        bond::BondRecord,
+        config::Config,
+        dispute::{DisputeClaim, DisputeReason, DisputeRecord, Ruling, SlashRecipient},
+        status::ErStatus,
+    },
+};
+use program_test::BanksClient;
+use sdk::{
+    account::{Account, AccountSharedData},
+    hash::Hash,
+    instruction::{AccountMeta, Instruction as SolanaInstruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    system_program,
+    transaction::{Transaction, TransactionError},
+};
+
+pub mod common;
+
+fn program(err: InstructionError) -> TransactionError {
+    TransactionError::InstructionError(0, err)
+}
+
+/// Accounts of register, sync and begin unregister instructions
+fn record_accounts(payer: Pubkey, is_signer: bool, pda: Pubkey) -> Vec<AccountMeta> {
+    let payer = match is_signer {
+        true => AccountMeta::new(payer, true),
+        false => AccountMeta::new_readonly(payer, false),
+    };
+    vec![
+        payer,
+        AccountMeta::new(pda, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(Config::pda().0, false),
+    ]
+}
+
+/// Accounts of complete unregister instruction
+fn exit_accounts(payer: Pubkey, is_signer: bool, pda: Pubkey) -> Vec<AccountMeta> {
+    let mut accounts = record_accounts(payer, is_signer, pda);
+    accounts[2] = AccountMeta::new(BondRecord::pda(&payer).0, false);
+    accounts[3] = AccountMeta::new_readonly(system_program::ID, false);
+    accounts
+}
+
+fn status_update(identity: Pubkey) -> Instruction {
+    Instruction::Sync(common::status_update(identity, 1, ErStatus::Draining))
+}
+
+/// Sends the instruction, paid by the first of the signers, and returns the error
+async fn send_err(
+    banks: &mut BanksClient,
+    signers: &[&Keypair],
+    ix: &Instruction,
+    accounts: Vec<AccountMeta>,
+) -> TransactionError {
+    let ix = SolanaInstruction::new_with_borsh(mdp::ID, ix, accounts);
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let payer = signers[0].pubkey();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer), signers, hash);
+    let result = common::process(banks, tx).await;
+    result.expect_err("instruction should fail").unwrap()
+}
+
+#[tokio::test]
+async fn test_register_errors() {
+    let TestEnv {
+        mut banks,
+        payer,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let id = identity.pubkey();
+    let pda = record.pda().0;
+    let ix = Instruction::Register(common::test_record(id, id));
+
+    let accounts = record_accounts(id, false, pda);
+    let err = send_err(&mut banks, &[&payer], &ix, accounts).await;
+    assert_eq!(err, program(InstructionError::MissingRequiredSignature));
+
+    // record is registered by someone else, than its identity
+    let accounts = record_accounts(payer.pubkey(), true, pda);
+    let err = send_err(&mut banks, &[&payer], &ix, accounts).await;
+    assert_eq!(err, common::custom(MdpError::IdentityMismatch));
+
+    let accounts = record_accounts(id, true, Pubkey::new_unique());
+    let err = send_err(&mut banks, &[&identity], &ix, accounts).await;
+    assert_eq!(err, common::custom(MdpError::InvalidPda));
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+    let mut record = common::test_record(id, id);
+    record.set_addr("https://127.0.0.1:9324".to_string());
+    let ix = Instruction::Register(record);
+    let err = send_err(
+        &mut banks,
+        &[&identity],
+        &ix,
+        record_accounts(id, true, pda),
+    )
+    .await;
+    assert_eq!(err, program(InstructionError::AccountAlreadyInitialized));
+}
+
+#[tokio::test]
+async fn test_sync_errors() {
+    let TestEnv {
+        mut banks,
+        payer,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let id = identity.pubkey();
+    let pda = record.pda().0;
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+    let ix = status_update(id);
+
+    let accounts = record_accounts(id, false, pda);
+    let err = send_err(&mut banks, &[&payer], &ix, accounts).await;
+    assert_eq!(err, program(InstructionError::MissingRequiredSignature));
+
+    let accounts = record_accounts(payer.pubkey(), true, pda);
+    let err = send_err(&mut banks, &[&payer], &ix, accounts).await;
+    assert_eq!(err, common::custom(MdpError::IdentityMismatch));
+
+    let accounts = record_accounts(id, true, Pubkey::new_unique());
+    let err = send_err(&mut banks, &[&identity], &ix, accounts).await;
+    assert_eq!(err, common::custom(MdpError::InvalidPda));
+
+    // node, which has never been registered
+    let stranger = Keypair::new();
+    let ix = status_update(stranger.pubkey());
+    let accounts = record_accounts(
+        stranger.pubkey(),
+        true,
+        common::record_pda(&stranger.pubkey()),
+    );
+    let err = send_err(&mut banks, &[&payer, &stranger], &ix, accounts).await;
+    assert_eq!(err, program(InstructionError::UninitializedAccount));
+}
+
+#[tokio::test]
+async fn test_unregister_errors() {
+    let TestEnv {
+        mut banks,
+        payer,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let id = identity.pubkey();
+    let pda = record.pda().0;
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let stranger = Keypair::new();
+    let stranger_pda = common::record_pda(&stranger.pubkey());
+    for (ix, accounts) in [
+        (
+            Instruction::BeginUnregister as fn(Pubkey) -> Instruction,
+            record_accounts as fn(Pubkey, bool, Pubkey) -> Vec<AccountMeta>,
+        ),
+        (Instruction::CompleteUnregister, exit_accounts),
+    ] {
+        let err = send_err(&mut banks, &[&payer], &ix(id), accounts(id, false, pda)).await;
+        assert_eq!(err, program(InstructionError::MissingRequiredSignature));
+
+        let payer_accounts = accounts(payer.pubkey(), true, pda);
+        let err = send_err(&mut banks, &[&payer], &ix(id), payer_accounts).await;
+        assert_eq!(err, common::custom(MdpError::IdentityMismatch));
+
+        let wrong_pda = accounts(id, true, Pubkey::new_unique());
+        let err = send_err(&mut banks, &[&identity], &ix(id), wrong_pda).await;
+        assert_eq!(err, common::custom(MdpError::InvalidPda));
+
+        let ix = ix(stranger.pubkey());
+        let accounts = accounts(stranger.pubkey(), true, stranger_pda);
+        let err = send_err(&mut banks, &[&payer, &stranger], &ix, accounts).await;
+        assert_eq!(err, program(InstructionError::UninitializedAccount));
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_record_account() {
+    let cases = [
+        // record is planted at its PDA by another program
+        (
+            Pubkey::new_unique(),
+            None,
+            InstructionError::InvalidAccountOwner,
+        ),
+        (
+            mdp::ID,
+            Some(vec![0xff; 64]),
+            InstructionError::InvalidAccountData,
+        ),
+    ];
+    for (owner, data, expected) in cases {
+        let TestEnv {
+            mut context,
+            mut banks,
+            identity,
+            record,
+            ..
+        } = common::setup().await;
+        let id = identity.pubkey();
+        let pda = record.pda().0;
+        let account = Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: data.unwrap_or_else(|| borsh::to_vec(&record).unwrap()),
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        context.set_account(&pda, &AccountSharedData::from(account));
+
+        let ix = status_update(id);
+        let err = send_err(
+            &mut banks,
+            &[&identity],
+            &ix,
+            record_accounts(id, true, pda),
+        )
+        .await;
+        assert_eq!(err, program(expected.clone()));
+
+        let ix = Instruction::BeginUnregister(id);
+        let err = send_err(
+            &mut banks,
+            &[&identity],
+            &ix,
+            record_accounts(id, true, pda),
+        )
+        .await;
+        assert_eq!(err, program(expected));
+    }
+}
+
+#[tokio::test]
+async fn test_exit_recipient_errors() {
+    let TestEnv {
+        mut context,
+        mut banks,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let pda = record.pda().0;
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+    let result = common::begin_unregister(&mut banks, &identity, pda).await;
+    assert_ok!(result, "error processing begin unregister transaction {}");
+    common::advance_clock(&mut context, UNBONDING_COOLDOWN_SECS).await;
+
+    // released lamports cannot be sent into the accounts being closed
+    let bond_pda = BondRecord::pda(&identity.pubkey()).0;
+    for recipient in [pda, bond_pda] {
+        let result =
+            common::complete_unregister_to(&mut banks, &identity, pda, Some(recipient)).await;
+        let err = result.expect_err("recipient should be rejected").unwrap();
+        assert_eq!(err, common::custom(MdpError::InvalidRecipient));
+    }
+}
+
+/// Accounts of resolve dispute instruction
+fn resolve_accounts(
+    resolver: Pubkey,
+    identity: Pubkey,
+    challenger: Pubkey,
+    challenger_account: Pubkey,
+    treasury: Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(resolver, true),
+        AccountMeta::new_readonly(Config::pda().0, false),
+        AccountMeta::new(DisputeRecord::pda(&identity, &challenger).0, false),
+        AccountMeta::new(BondRecord::pda(&identity).0, false),
+        AccountMeta::new(challenger_account, false),
+        AccountMeta::new(treasury, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ]
+}
+
+#[tokio::test]
+async fn test_dispute_errors() {
+    let TestEnv {
+        mut banks,
+        payer,
+        admin,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let id = identity.pubkey();
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+    let result = common::bond(&mut banks, &identity, LAMPORTS_PER_SOL / 2).await;
+    assert_ok!(result, "error processing bond transaction {}");
+
+    let claim = |stake| DisputeClaim {
+        identity: id,
+        reason: DisputeReason::WrongEndpoint,
+        evidence: Hash::new_from_array([9; 32]),
+        stake,
+    };
+    // ER node cannot dispute itself, nor can anyone dispute it for free
+    let result = common::open_dispute(&mut banks, &identity, claim(1)).await;
+    let err = result
+        .expect_err("self dispute should be rejected")
+        .unwrap();
+    assert_eq!(err, common::custom(MdpError::InvalidDisputeClaim));
+    let result = common::open_dispute(&mut banks, &payer, claim(0)).await;
+    let err = result
+        .expect_err("dispute without stake should be rejected")
+        .unwrap();
+    assert_eq!(err, common::custom(MdpError::InvalidDisputeClaim));
+
+    let result = common::open_dispute(&mut banks, &payer, claim(LAMPORTS_PER_SOL / 10)).await;
+    assert_ok!(result, "error processing open dispute transaction {}");
+
+    let ix = Instruction::ResolveDispute(Ruling {
+        upheld: true,
+        slash_to: SlashRecipient::Treasury,
+    });
+    let challenger = payer.pubkey();
+    let accounts = resolve_accounts(
+        admin.pubkey(),
+        id,
+        challenger,
+        challenger,
+        Pubkey::new_unique(),
+    );
+    let err = send_err(&mut banks, &[&admin], &ix, accounts).await;
+    assert_eq!(err, common::custom(MdpError::TreasuryMismatch));
+
+    let stranger = Pubkey::new_unique();
+    let accounts = resolve_accounts(admin.pubkey(), id, challenger, stranger, TREASURY);
+    let err = send_err(&mut banks, &[&admin], &ix, accounts).await;
+    assert_eq!(err, common::custom(MdpError::ChallengerMismatch));
+}
+
+#[tokio::test]
+async fn test_config_errors() {
+    let TestEnv {
+        mut banks, admin, ..
+    } = common::setup().await;
+
+    // program data account has to be the one of the program
+    let ix = Instruction::InitConfig(common::test_config(&admin));
+    let accounts = vec![
+        AccountMeta::new(admin.pubkey(), true),
+        AccountMeta::new(Config::pda().0, false),
+        AccountMeta::new_readonly(Pubkey::new_unique(), false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    let err = send_err(&mut banks, &[&admin], &ix, accounts).await;
+    assert_eq!(err, common::custom(MdpError::InvalidProgramData));
+
+    // upgrade authority cannot hand the config over to another admin on initialization
+    let mut config = common::test_config(&admin);
+    config.admin = Pubkey::new_unique();
+    let result = common::init_config(&mut banks, &admin, config).await;
+    let err = result.expect_err("config should be rejected").unwrap();
+    assert_eq!(err, common::custom(MdpError::InvalidConfig));
+
+    // attester set is bounded
+    let mut config = common::test_config(&admin);
+    config.attesters = vec![Pubkey::new_unique(); MAX_ATTESTERS + 1];
+    let result = common::update_config(&mut banks, &admin, config).await;
+    let err = result
+        .expect_err("too many attesters should be rejected")
+        .unwrap();
+    assert_eq!(err, common::custom(MdpError::InvalidConfig));
+
+    // negative cooldown would let ER nodes exit right away
+    let mut config = common::test_config(&admin);
+    config.unbonding_cooldown_secs = -1;
+    let result = common::init_config(&mut banks, &admin, config.clone()).await;
+    let err = result
+        .expect_err("negative cooldown should be rejected")
+        .unwrap();
+    assert_eq!(err, common::custom(MdpError::InvalidConfig));
+    let result = common::update_config(&mut banks, &admin, config).await;
+    let err = result
+        .expect_err("negative cooldown should be rejected")
+        .unwrap();
+    assert_eq!(err, common::custom(MdpError::InvalidConfig));
+}
+
+#[tokio::test]
+async fn test_name_errors() {
+    let TestEnv {
+        mut banks, payer, ..
+    } = common::setup().await;
+    let result = common::claim_name(&mut banks, &payer, "acme", None, None).await;
+    assert_ok!(result, "error processing claim name transaction {}");
+    let result = common::claim_name(&mut banks, &payer, "node.acme", Some(&payer), None).await;
+    assert_ok!(result, "error processing claim name transaction {}");
+
+    let result = common::release_name(&mut banks, &payer, "acme", None).await;
+    let err = result
+        .expect_err("namespace should not be released")
+        .unwrap();
+    assert_eq!(err, common::custom(MdpError::NameHasChildren));
+}
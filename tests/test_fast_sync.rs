@@ -0,0 +1,109 @@
+//! Compares compute units of the fast sync path against the regular one, so it's only
+//! run with the feature, against both builds of the program (see README)
+
+#![cfg(feature = "zero-copy")]
+
+use common::TestEnv;
+use mdp::{
+    instructions::{sync::SyncInstruction, Instruction},
+    state::{config::Config, metrics::LoadMetrics, record::ErRecord, status::ErStatus},
+};
+use program_test::BanksClient;
+use sdk::{
+    instruction::{AccountMeta, Instruction as SolanaInstruction},
+    signature::Keypair,
+    signer::Signer,
+    system_program,
+    transaction::Transaction,
+};
+
+pub mod common;
+
+/// Name of the program build without the feature, which always takes the regular path
+const REGULAR_BUILD: &str = "mdp_regular";
+
+/// Sync of the fixed size fields only, which can be applied to the record in place
+fn status_sync(identity: &Keypair, sequence: u64) -> SyncInstruction {
+    let SyncInstruction::V1(mut sync) =
+        common::status_update(identity.pubkey(), sequence, ErStatus::Draining);
+    sync.block_time_ms = Some(40);
+    sync.load_average = Some(3_000_000);
+    sync.metrics = Some(LoadMetrics {
+        tps: 2_500,
+        ..Default::default()
+    });
+    SyncInstruction::V1(sync)
+}
+
+/// Simulates the transaction to measure its compute units, then processes it
+async fn process(banks: &mut BanksClient, identity: &Keypair, ix: SolanaInstruction) -> u64 {
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&identity.pubkey()), &[identity], hash);
+
+    let result = banks.simulate_transaction(tx.clone()).await;
+    let simulation = assert_ok!(result, "error simulating sync transaction {}");
+    assert!(matches!(simulation.result, Some(Ok(()))));
+    let units = simulation.simulation_details.unwrap().units_consumed;
+
+    let result = banks.process_transaction(tx).await;
+    assert_ok!(result, "error processing sync transaction {}");
+    units
+}
+
+/// Sends the update as standalone sync, which is applied in place if possible
+async fn sync(banks: &mut BanksClient, identity: &Keypair, sync: SyncInstruction) -> u64 {
+    let ix = Instruction::Sync(sync);
+    let ix = SolanaInstruction::new_with_borsh(
+        mdp::ID,
+        &ix,
+        vec![
+            AccountMeta::new(identity.pubkey(), true),
+            AccountMeta::new(common::record_pda(&identity.pubkey()), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(Config::pda().0, false),
+        ],
+    );
+    process(banks, identity, ix).await
+}
+
+/// Registers ER node and sends the same status update to it, returning the compute units
+/// consumed by the sync along with the synced record
+async fn register_and_sync(env: TestEnv) -> (u64, ErRecord) {
+    let TestEnv {
+        mut banks,
+        identity,
+        record,
+        context: _context,
+        ..
+    } = env;
+    let pda = record.pda().0;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let units = sync(&mut banks, &identity, status_sync(&identity, 1)).await;
+    let record = common::load_record(&mut banks, pda).await.unwrap();
+    assert_eq!(record.sequence(), 1);
+    assert_eq!(record.status(), ErStatus::Draining);
+    assert_eq!(record.block_time_ms(), 40);
+    assert_eq!(record.load_average(), 3_000_000);
+    assert_eq!(record.metrics().tps, 2_500);
+    (units, record)
+}
+
+#[tokio::test]
+async fn test_fast_sync_compute_units() {
+    let (fast, fast_record) = register_and_sync(common::setup().await).await;
+    let (regular, regular_record) =
+        register_and_sync(common::setup_build(REGULAR_BUILD).await).await;
+
+    assert_eq!(
+        fast_record.heartbeats(),
+        regular_record.heartbeats(),
+        "both paths should record the heartbeat in the same way"
+    );
+    assert!(
+        fast < regular,
+        "in place sync ({fast} CUs) should be cheaper than regular one ({regular} CUs)"
+    );
+}
@@ -0,0 +1,104 @@
+use common::{TestEnv, FEE_NOTICE_SLOTS};
+use sdk::clock::Clock;
+
+pub mod common;
+
+#[tokio::test]
+async fn test_fee_increase_notice() {
+    let TestEnv {
+        mut context,
+        mut banks,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let pda = record.pda().0;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let mut record = common::load_record(&mut banks, pda).await.unwrap();
+    let current = record.fees().clone();
+    let mut raised = current.clone();
+    raised.lamports_per_signature *= 2;
+
+    let result = banks.get_sysvar::<Clock>().await;
+    let announced_at = assert_ok!(result, "error querying clock sysvar {}").slot;
+    record.set_fees(raised.clone());
+    record.set_sequence(1);
+    let result = common::sync(&mut banks, &identity, record).await;
+    assert_ok!(result, "error processing sync info transaction {}");
+
+    // increase is held back for the notice period
+    let mut record = common::load_record(&mut banks, pda).await.unwrap();
+    assert_eq!(*record.fees(), current);
+    let pending = record
+        .pending_fees()
+        .expect("fee increase should be pending");
+    assert_eq!(pending.fees, raised);
+    let effective_slot = pending.effective_slot;
+    assert!(effective_slot >= announced_at + FEE_NOTICE_SLOTS);
+    assert_eq!(*record.effective_fees(effective_slot - 1), current);
+    assert_eq!(*record.effective_fees(effective_slot), raised);
+
+    // unrelated sync during notice period doesn't restart it
+    record.set_load_average(42);
+    record.set_sequence(2);
+    let result = common::sync(&mut banks, &identity, record).await;
+    assert_ok!(result, "error processing sync info transaction {}");
+    let mut record = common::load_record(&mut banks, pda).await.unwrap();
+    assert_eq!(*record.fees(), current);
+    assert_eq!(
+        record.pending_fees().unwrap().effective_slot,
+        effective_slot
+    );
+
+    // once notice period passes, next sync settles the increase
+    let result = context.warp_to_slot(effective_slot);
+    assert_ok!(result, "error warping to slot {:?}");
+    record.set_sequence(3);
+    let result = common::sync(&mut banks, &identity, record).await;
+    assert_ok!(result, "error processing sync info transaction {}");
+    let record = common::load_record(&mut banks, pda).await.unwrap();
+    assert_eq!(*record.fees(), raised);
+    assert!(record.pending_fees().is_none());
+}
+
+#[tokio::test]
+async fn test_fee_decrease_cancels_pending_increase() {
+    let TestEnv {
+        mut banks,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let pda = record.pda().0;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let mut record = common::load_record(&mut banks, pda).await.unwrap();
+    let current = record.fees().clone();
+    let mut raised = current.clone();
+    raised.micro_lamports_per_cu *= 2;
+    record.set_fees(raised);
+    record.set_sequence(1);
+    let result = common::sync(&mut banks, &identity, record).await;
+    assert_ok!(result, "error processing sync info transaction {}");
+
+    let mut record = common::load_record(&mut banks, pda).await.unwrap();
+    assert!(record.pending_fees().is_some());
+
+    // decrease applies immediately, superseding the pending increase
+    let mut lowered = current.clone();
+    lowered.lamports_per_signature /= 2;
+    record.set_fees(lowered.clone());
+    record.set_pending_fees(None);
+    record.set_sequence(2);
+    let result = common::sync(&mut banks, &identity, record).await;
+    assert_ok!(result, "error processing sync info transaction {}");
+
+    let record = common::load_record(&mut banks, pda).await.unwrap();
+    assert_eq!(*record.fees(), lowered);
+    assert!(record.pending_fees().is_none());
+}
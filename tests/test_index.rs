@@ -0,0 +1,162 @@
+use borsh::BorshDeserialize;
+use common::{FleetEnv, TestEnv};
+use mdp::{
+    error::MdpError,
+    state::{
+        features::{Feature, FeaturesSet},
+        index::{IndexKey, IndexPage},
+        record::{CountryCode, ErRecord},
+    },
+};
+use program_test::BanksClient;
+use sdk::{
+    instruction::InstructionError, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    transaction::TransactionError,
+};
+
+pub mod common;
+
+const COUNTRIES: [&str; 3] = ["083", "276", "840"];
+
+/// Lists identities of all ER nodes under the given key, fetching every page of the index
+async fn list(banks: &mut BanksClient, key: IndexKey) -> Vec<Pubkey> {
+    let mut identities = Vec::new();
+    for pda in key.pages().collect::<Vec<_>>() {
+        let result = banks.get_account(pda).await;
+        let acc = assert_ok!(result, "error querying index page from banks {}");
+        if let Some(acc) = acc {
+            let result = IndexPage::try_from_slice(&acc.data);
+            let page = assert_ok!(result, "error deserializing index page {}");
+            assert_eq!(page.key, key);
+            identities.extend(page.identities);
+        }
+    }
+    identities
+}
+
+/// Checks that identity is listed exactly under the keys the on chain record belongs to
+async fn assert_consistent(banks: &mut BanksClient, identity: &Pubkey) {
+    let record = common::load_record(banks, common::record_pda(identity)).await;
+    let expected = record.as_ref().map(IndexKey::of).unwrap_or_default();
+    let countries = COUNTRIES.map(|c| IndexKey::Country(CountryCode::from(c)));
+    let features = Feature::ALL.map(IndexKey::Feature);
+    for key in countries.into_iter().chain(features) {
+        let listed = list(banks, key).await.contains(identity);
+        assert_eq!(
+            listed,
+            expected.contains(&key),
+            "{key:?} index of {identity}"
+        );
+    }
+}
+
+async fn register(banks: &mut BanksClient, identity: &Keypair) {
+    let record = common::test_record(identity.pubkey(), identity.pubkey());
+    let result = common::register(banks, record, identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+    assert_consistent(banks, &identity.pubkey()).await;
+}
+
+/// Applies the update to the on chain record of ER node and syncs it
+async fn sync(banks: &mut BanksClient, identity: &Keypair, update: impl FnOnce(&mut ErRecord)) {
+    let mut record = load(banks, identity).await;
+    update(&mut record);
+    record.set_sequence(record.sequence() + 1);
+    let result = common::sync(banks, identity, record).await;
+    assert_ok!(result, "error processing sync transaction {}");
+    assert_consistent(banks, &identity.pubkey()).await;
+}
+
+async fn load(banks: &mut BanksClient, identity: &Keypair) -> ErRecord {
+    let pda = common::record_pda(&identity.pubkey());
+    common::load_record(banks, pda)
+        .await
+        .expect("ER record should exist")
+}
+
+#[tokio::test]
+async fn test_index_consistency() {
+    let FleetEnv {
+        mut banks,
+        authority: relayer,
+        identities,
+        ..
+    } = common::setup_fleet(2).await;
+    let [identity, other] = &identities[..] else {
+        unreachable!()
+    };
+    let identity = identity.insecure_clone();
+
+    register(&mut banks, &identity).await;
+
+    // moving to another country and enabling more features
+    sync(&mut banks, &identity, |r| {
+        r.set_country_code(CountryCode::from("276"));
+        r.set_features(
+            FeaturesSet::default()
+                .activate(Feature::Randomness)
+                .activate(Feature::HighResClock),
+        );
+    })
+    .await;
+
+    // dropping a feature, while keeping the country
+    sync(&mut banks, &identity, |r| {
+        r.set_features(FeaturesSet::default().activate(Feature::HighResClock))
+    })
+    .await;
+
+    // sync not touching indexed fields doesn't need any index pages
+    sync(&mut banks, &identity, |r| r.set_block_time_ms(100)).await;
+
+    // relayed sync maintains indexes as well
+    let mut record = load(&mut banks, &identity).await;
+    record.set_country_code(CountryCode::from("840"));
+    record.set_sequence(record.sequence() + 1);
+    let result = common::relayed_sync(&mut banks, &relayer, &identity, record).await;
+    assert_ok!(result, "error processing relayed sync transaction {}");
+    assert_consistent(&mut banks, &identity.pubkey()).await;
+
+    // other node in the same country is listed alongside, possibly on another page
+    register(&mut banks, other).await;
+    let mut listed = list(&mut banks, IndexKey::Country(CountryCode::from("083"))).await;
+    assert_eq!(listed, vec![other.pubkey()]);
+    listed = list(&mut banks, IndexKey::Feature(Feature::HighResClock)).await;
+    assert_eq!(listed, vec![identity.pubkey()]);
+
+    // exiting node is removed from all of the indexes right away
+    let pda = common::record_pda(&identity.pubkey());
+    let result = common::begin_unregister(&mut banks, &identity, pda).await;
+    assert_ok!(result, "error processing begin unregister transaction {}");
+    assert_consistent(&mut banks, &identity.pubkey()).await;
+    assert!(
+        list(&mut banks, IndexKey::Country(CountryCode::from("840")))
+            .await
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_index_page_missing() {
+    let TestEnv {
+        mut banks,
+        identity,
+        ..
+    } = common::setup().await;
+
+    register(&mut banks, &identity).await;
+
+    let mut record = load(&mut banks, &identity).await;
+    record.set_features(FeaturesSet::default());
+    record.set_sequence(1);
+    let result = common::sync_with_pages(&mut banks, &identity, record, vec![]).await;
+    let err = result.expect_err("sync without affected index page should be rejected");
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MdpError::MissingIndexPage as u32)
+        )
+    );
+    assert_consistent(&mut banks, &identity.pubkey()).await;
+}
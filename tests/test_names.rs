@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use borsh::BorshDeserialize;
+use common::FleetEnv;
+use mdp::state::name::{forward_lookup, resolve, reverse_lookup, NameRecord, ReverseNameRecord};
+use program_test::BanksClient;
+use sdk::{pubkey::Pubkey, signer::Signer};
+
+pub mod common;
+
+const NAMESPACE: &str = "magicblock";
+const NAME: &str = "eu-west-1.magicblock";
+
+/// Registers two ER nodes and claims the namespace, owned by fleet authority
+async fn setup() -> FleetEnv {
+    let mut env = common::setup_fleet(2).await;
+    for identity in &env.identities {
+        let record = common::test_record(identity.pubkey(), identity.pubkey());
+        let result = common::register(&mut env.banks, record, identity).await;
+        assert_ok!(result, "error processing register transaction {}");
+    }
+    let result = common::claim_name(&mut env.banks, &env.authority, NAMESPACE, None, None).await;
+    assert_ok!(result, "error processing claim namespace transaction {}");
+    env
+}
+
+async fn fetch_name(banks: &mut BanksClient, address: Pubkey) -> Option<NameRecord> {
+    let result = banks.get_account(address).await;
+    let acc = assert_ok!(result, "error querying name PDA from banks {}")?;
+    let result = NameRecord::try_from_slice(&acc.data);
+    Some(assert_ok!(result, "error deserializing name record {}"))
+}
+
+#[tokio::test]
+async fn test_claim_name() {
+    let FleetEnv {
+        mut banks,
+        authority,
+        identities,
+        ..
+    } = setup().await;
+    let identity = &identities[0];
+
+    let result = common::claim_name(
+        &mut banks,
+        &authority,
+        NAME,
+        Some(&authority),
+        Some(identity),
+    )
+    .await;
+    assert_ok!(result, "error processing claim name transaction {}");
+
+    let mut names = HashMap::new();
+    for name in [NAME, NAMESPACE] {
+        let address = forward_lookup(name).unwrap();
+        names.insert(address, fetch_name(&mut banks, address).await.unwrap());
+    }
+    let registered = |address: &Pubkey| *address == common::record_pda(&identity.pubkey());
+    let resolved = resolve(
+        " EU-West-1.MagicBlock",
+        |a| names.get(a).cloned(),
+        registered,
+    );
+    assert_eq!(resolved, Some(identity.pubkey()));
+    let resolved = resolve(NAMESPACE, |a| names.get(a).cloned(), registered);
+    assert_eq!(resolved, None);
+
+    let namespace = &names[&NameRecord::pda(NAMESPACE).0];
+    assert_eq!(namespace.children, 1);
+    let name = &names[&NameRecord::pda(NAME).0];
+    assert_eq!(name.owner, authority.pubkey());
+    assert_eq!(name.parent, Some(NameRecord::pda(NAMESPACE).0));
+
+    let result = banks.get_account(reverse_lookup(&identity.pubkey())).await;
+    let acc = assert_ok!(result, "error querying reverse name PDA from banks {}");
+    let result = ReverseNameRecord::try_from_slice(&acc.unwrap().data);
+    let reverse = assert_ok!(result, "error deserializing reverse name record {}");
+    assert_eq!(reverse.name, NAME);
+
+    // the name is already taken by another ER node
+    let other = &identities[1];
+    let result =
+        common::claim_name(&mut banks, &authority, NAME, Some(&authority), Some(other)).await;
+    assert!(result.is_err(), "name should be unique");
+
+    // names should be claimed in normalized form only
+    let result = common::claim_name(&mut banks, &authority, "Acme", None, None).await;
+    assert!(result.is_err(), "non normalized name should be rejected");
+}
+
+#[tokio::test]
+async fn test_child_name_requires_parent_owner() {
+    let FleetEnv {
+        mut banks,
+        identities,
+        ..
+    } = setup().await;
+    let identity = &identities[1];
+
+    // ER node tries to issue itself a name in the namespace it doesn't own
+    let name = "node1.magicblock";
+    let result =
+        common::claim_name(&mut banks, identity, name, Some(identity), Some(identity)).await;
+    assert!(
+        result.is_err(),
+        "child name should be authorized by parent owner"
+    );
+
+    // child of non-existent parent cannot be claimed either
+    let name = "node1.acme";
+    let result =
+        common::claim_name(&mut banks, identity, name, Some(identity), Some(identity)).await;
+    assert!(result.is_err(), "parent name should exist");
+}
+
+#[tokio::test]
+async fn test_transfer_and_release_name() {
+    let FleetEnv {
+        mut banks,
+        authority,
+        identities,
+        // keeps producing new blockhashes
+        context: _context,
+    } = setup().await;
+    let identity = &identities[0];
+    let new_owner = &identities[1];
+
+    let result = common::claim_name(
+        &mut banks,
+        &authority,
+        NAME,
+        Some(&authority),
+        Some(identity),
+    )
+    .await;
+    assert_ok!(result, "error processing claim name transaction {}");
+
+    let result = common::transfer_name(&mut banks, &authority, NAME, new_owner.pubkey()).await;
+    assert_ok!(result, "error processing transfer name transaction {}");
+
+    let address = NameRecord::pda(NAME).0;
+    let name = fetch_name(&mut banks, address).await.unwrap();
+    assert_eq!(name.owner, new_owner.pubkey());
+    assert_eq!(name.identity, Some(identity.pubkey()));
+
+    // previous owner is no longer allowed to release the name
+    let result = common::release_name(&mut banks, &authority, NAME, name.identity).await;
+    assert!(result.is_err(), "only name owner can release it");
+
+    // namespace cannot be released while it has children
+    let result = common::release_name(&mut banks, &authority, NAMESPACE, None).await;
+    assert!(result.is_err(), "name with children cannot be released");
+
+    let result = common::release_name(&mut banks, new_owner, NAME, name.identity).await;
+    assert_ok!(result, "error processing release name transaction {}");
+
+    assert!(fetch_name(&mut banks, address).await.is_none());
+    let result = banks.get_account(reverse_lookup(&identity.pubkey())).await;
+    let acc = assert_ok!(result, "error querying reverse name PDA from banks {}");
+    assert!(acc.is_none(), "reverse name record hasn't been removed");
+
+    // the same transaction has been rejected above
+    common::refresh_blockhash(&mut banks).await;
+    let result = common::release_name(&mut banks, &authority, NAMESPACE, None).await;
+    assert_ok!(result, "error processing release namespace transaction {}");
+}
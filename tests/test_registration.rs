@@ -3,6 +3,7 @@ use common::TestEnv;
 use mdp::state::{
     features::{Feature, FeaturesSet},
     record::ErRecord,
+    reputation::Heartbeats,
 };
 use sdk::{account::Account, signer::Signer};
 
@@ -13,9 +14,12 @@ async fn test_registration() {
     let TestEnv {
         mut banks,
         identity,
-        record,
+        mut record,
         ..
     } = common::setup().await;
+    // neither sync sequence nor heartbeats can be carried into registration
+    record.set_sequence(42);
+    record.heartbeat(u64::MAX / 2);
     let addr = record.addr().to_owned();
     let pda = record.pda().0;
 
@@ -36,4 +40,8 @@ async fn test_registration() {
         FeaturesSet::default().activate(Feature::Randomness)
     );
     assert_eq!(record.addr(), addr);
+    assert_eq!(record.sequence(), 0);
+    let mut heartbeats = Heartbeats::default();
+    heartbeats.record(record.registered_slot());
+    assert_eq!(*record.heartbeats(), heartbeats);
 }
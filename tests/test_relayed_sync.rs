@@ -0,0 +1,71 @@
+use borsh::BorshDeserialize;
+use common::TestEnv;
+use mdp::state::{record::ErRecord, status::ErStatus};
+
+pub mod common;
+
+#[tokio::test]
+async fn test_relayed_sync() {
+    let TestEnv {
+        mut banks,
+        payer,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let pda = record.pda().0;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let mut record = assert_ok!(result, "error deserializing registered record {}");
+
+    record.set_load_average(3_100_000);
+    record.set_status(ErStatus::Draining);
+    record.set_sequence(1);
+    let result = common::relayed_sync(&mut banks, &payer, &identity, record).await;
+    assert_ok!(result, "error processing relayed sync transaction {}");
+
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let record = assert_ok!(result, "error deserializing record after relayed sync {}");
+    assert_eq!(record.load_average(), 3_100_000);
+    assert_eq!(record.status(), ErStatus::Draining);
+    assert_eq!(record.sequence(), 1);
+
+    // same signed message relayed by someone else should be rejected
+    let result = common::relayed_sync(&mut banks, &identity, &identity, record).await;
+    assert!(result.is_err(), "replayed relayed sync should be rejected");
+}
+
+#[tokio::test]
+async fn test_relayed_sync_wrong_signer() {
+    let TestEnv {
+        mut banks,
+        payer,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let pda = record.pda().0;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let mut record = assert_ok!(result, "error deserializing registered record {}");
+    record.set_sequence(1);
+
+    // relayer signs the message instead of ER node identity
+    let result = common::relayed_sync(&mut banks, &payer, &payer, record).await;
+    assert!(
+        result.is_err(),
+        "relayed sync not signed by identity should be rejected"
+    );
+}
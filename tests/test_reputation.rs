@@ -0,0 +1,83 @@
+use borsh::BorshDeserialize;
+use common::TestEnv;
+use mdp::state::{
+    attestation::{AttestationRecord, Observation},
+    metrics::MAX_BPS,
+    record::ErRecord,
+    reputation::{score, ReputationInputs, ReputationRecord},
+};
+use sdk::{clock::Clock, signer::Signer};
+
+pub mod common;
+
+#[tokio::test]
+async fn test_update_reputation() {
+    let TestEnv {
+        mut banks,
+        payer,
+        admin,
+        identity,
+        record,
+        // keeps producing new blockhashes
+        context: _context,
+        ..
+    } = common::setup().await;
+    let pda = record.pda().0;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let result = banks.get_sysvar::<Clock>().await;
+    let slot = assert_ok!(result, "error querying clock sysvar {}").slot;
+
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let record = assert_ok!(result, "error deserializing record {}");
+    // registration slot and heartbeats are maintained by the program
+    assert!(record.registered_slot() <= slot);
+    assert_eq!(record.heartbeats().uptime_bps(slot, slot), MAX_BPS);
+
+    // node without attestations still gets a score
+    let result = common::update_reputation(&mut banks, &payer, identity.pubkey()).await;
+    assert_ok!(result, "error processing update reputation transaction {}");
+
+    let reputation_pda = ReputationRecord::pda(&identity.pubkey()).0;
+    let result = banks.get_account(reputation_pda).await;
+    let acc = assert_ok!(result, "error querying reputation PDA from banks {}");
+    let result = ReputationRecord::try_from_slice(&acc.unwrap().data);
+    let unattested = assert_ok!(result, "error deserializing reputation record {}");
+    assert_eq!(unattested.identity, identity.pubkey());
+
+    for success in [true, true, true, false] {
+        let observation = Observation {
+            identity: identity.pubkey(),
+            slot,
+            latency_ms: 25,
+            success,
+        };
+        // repeated observations would otherwise be deduplicated
+        common::refresh_blockhash(&mut banks).await;
+        let result = common::attest(&mut banks, &admin, observation).await;
+        assert_ok!(result, "error processing attest transaction {}");
+    }
+
+    let result = common::update_reputation(&mut banks, &payer, identity.pubkey()).await;
+    assert_ok!(result, "error processing update reputation transaction {}");
+
+    let result = banks.get_account(reputation_pda).await;
+    let acc = assert_ok!(result, "error querying reputation PDA from banks {}");
+    let result = ReputationRecord::try_from_slice(&acc.unwrap().data);
+    let reputation = assert_ok!(result, "error deserializing reputation record {}");
+
+    // on chain score matches the library computation
+    let attestation_pda = AttestationRecord::pda(&identity.pubkey()).0;
+    let result = banks.get_account(attestation_pda).await;
+    let acc = assert_ok!(result, "error querying attestation PDA from banks {}");
+    let result = AttestationRecord::try_from_slice(&acc.unwrap().data);
+    let attestation = assert_ok!(result, "error deserializing attestation record {}");
+    let inputs = ReputationInputs::collect(&record, Some(&attestation), reputation.slot);
+    assert_eq!(inputs.success_rate_bps, Some(7_500));
+    assert_eq!(reputation.score, score(&inputs));
+    assert!(reputation.score > unattested.score);
+}
@@ -1,10 +1,36 @@
 use borsh::BorshDeserialize;
 use common::TestEnv;
-use mdp::state::{record::ErRecord, status::ErStatus};
-use sdk::account::Account;
+use mdp::{
+    error::MdpError,
+    instructions::version::v0::SyncRecordV0,
+    pinning::PinningPolicy,
+    state::{
+        config::Config,
+        features::{Feature, FeaturesSet},
+        fees::{FeatureSurcharge, TxProfile},
+        geo::{GeoPoint, Region},
+        maintenance::{MaintenanceReason, MaintenanceWindow},
+        metrics::LoadMetrics,
+        record::{CountryCode, ErRecord, V1_TAG},
+        semver::SemVer,
+        status::ErStatus,
+        tls::SpkiFingerprint,
+        version::v0::RecordV0,
+    },
+};
+use sdk::{
+    account::{Account, AccountSharedData},
+    instruction::{AccountMeta, Instruction as SolanaInstruction, InstructionError},
+    signer::Signer,
+    system_program,
+    transaction::{Transaction, TransactionError},
+};
 
 pub mod common;
 
+/// Index of `Instruction::Sync` in its Borsh encoding
+const SYNC_TAG: u8 = 2;
+
 #[tokio::test]
 async fn test_sync_info() {
     let TestEnv {
@@ -28,11 +54,28 @@ async fn test_sync_info() {
 
     const NEW_BLOCK_TIME: u16 = 1000;
     const NEW_ADDR: &str = "https://127.145.24.55:9324";
+    const SPKI: &[u8] = b"DER encoded public key of ER node";
 
     record.set_block_time_ms(NEW_BLOCK_TIME);
     record.set_addr(NEW_ADDR.to_string());
+    record.set_tls_fingerprint(Some(SpkiFingerprint::of(SPKI)));
+    let location = GeoPoint::from_degrees(39.04, -77.49);
+    record.set_region(Some(Region::NorthAmericaEast));
+    record.set_location(Some(location));
     record.set_status(ErStatus::Draining);
     record.set_load_average(2_200_000);
+    let metrics = LoadMetrics {
+        tps: 4_000,
+        latency_p50_ms: 35,
+        latency_p99_ms: 180,
+        active_delegations: 7_500,
+        max_delegations: 10_000,
+        memory_pressure_bps: 5_000,
+    };
+    record.set_metrics(metrics);
+    record.set_software_version(SemVer::new(0, 2, 1));
+    record.set_protocol_version(SemVer::new(1, 1, 0));
+    record.set_sequence(1);
 
     let result = common::sync(&mut banks, &identity, record).await;
     assert_ok!(result, "error processing sync info transaction {}");
@@ -51,19 +94,308 @@ async fn test_sync_info() {
         "error querying registration PDA post modification {}"
     );
     assert_eq!(record.addr(), NEW_ADDR);
+    let policy = PinningPolicy::from_record(&record).expect("certificate should be pinned");
+    assert_eq!(policy.addr(), NEW_ADDR);
+    assert!(policy.verify(SPKI).is_ok());
+    assert!(policy.verify(b"public key of impostor").is_err());
     assert_eq!(record.block_time_ms(), NEW_BLOCK_TIME);
+    assert_eq!(record.region(), Some(Region::NorthAmericaEast));
+    assert_eq!(record.location(), Some(&location));
     assert_eq!(record.status(), ErStatus::Draining);
     assert_eq!(record.load_average(), 2_200_000);
+    assert_eq!(record.sequence(), 1);
+    assert_eq!(*record.metrics(), metrics);
+    assert_eq!(record.utilization(), 7_500);
+    assert_eq!(record.software_version(), SemVer::new(0, 2, 1));
+    assert!(record
+        .protocol_version()
+        .is_compatible_with(&SemVer::new(1, 0, 0)));
+
+    let result = common::begin_unregister(&mut banks, &identity, pda).await;
+
+    assert_ok!(result, "error processing begin unregister transaction {}");
+
+    let result = banks.get_account(pda).await;
+
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let record = assert_ok!(result, "error deserializing record {}");
+    assert_eq!(record.status(), ErStatus::Deregistering);
+}
+
+#[tokio::test]
+async fn test_sync_stale_sequence() {
+    let TestEnv {
+        mut banks,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let pda = record.pda().0;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let mut record = assert_ok!(result, "error querying registration PDA from banks {}");
+
+    record.set_sequence(5);
+    record.set_status(ErStatus::Offline);
+    let result = common::sync(&mut banks, &identity, record).await;
+    assert_ok!(result, "error processing sync info transaction {}");
+
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let mut record = assert_ok!(result, "error querying registration PDA from banks {}");
+
+    // older update arriving out of order must not overwrite the newer one
+    record.set_sequence(4);
+    record.set_status(ErStatus::Active);
+    let result = common::sync(&mut banks, &identity, record).await;
+    let err = result.expect_err("stale sync should be rejected").unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MdpError::StaleSequence as u32)
+        )
+    );
+
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let record = assert_ok!(result, "error querying registration PDA from banks {}");
+    assert_eq!(record.status(), ErStatus::Offline);
+    assert_eq!(record.sequence(), 5);
+}
+
+#[tokio::test]
+async fn test_sync_legacy_layout() {
+    let TestEnv {
+        mut banks,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let pda = record.pda().0;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    // sync in the original layout carries no sequence number, so it can't be ordered
+    let legacy = SyncRecordV0 {
+        identity: identity.pubkey(),
+        status: Some(ErStatus::Offline),
+        block_time_ms: None,
+        base_fee: Some(1000),
+        features: None,
+        load_average: None,
+        country_code: None,
+        addr: None,
+    };
+    let data = borsh::to_vec(&(SYNC_TAG, 0u8, legacy)).unwrap();
+    let accounts = vec![
+        AccountMeta::new(identity.pubkey(), true),
+        AccountMeta::new(pda, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(Config::pda().0, false),
+    ];
+    let ix = SolanaInstruction::new_with_bytes(mdp::ID, &data, accounts);
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&identity.pubkey()), &[&identity], hash);
+    let err = banks.process_transaction(tx).await;
+    let err = err.expect_err("legacy sync should be rejected").unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MdpError::StaleSequence as u32)
+        )
+    );
+
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let record = assert_ok!(result, "error querying registration PDA from banks {}");
+    assert_eq!(record.status(), ErStatus::Active);
+}
+
+#[tokio::test]
+async fn test_sync_maintenance() {
+    let TestEnv {
+        mut banks,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let pda = record.pda().0;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let mut record = assert_ok!(result, "error querying registration PDA from banks {}");
+
+    const START: i64 = 1_800_000_000;
+    const END: i64 = START + 3600;
+    let window = MaintenanceWindow {
+        start: START,
+        end: END,
+        reason: MaintenanceReason::Hardware,
+    };
+    record.set_maintenance(vec![window]);
+    // longer address along with the window grows the record
+    record.set_addr("https://er-node-eu-west-1.example.com:9324/".to_string());
+    record.set_sequence(1);
+    let result = common::sync(&mut banks, &identity, record).await;
+    assert_ok!(result, "error processing sync info transaction {}");
+
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let mut record = assert_ok!(result, "error querying registration PDA from banks {}");
+    assert_eq!(record.maintenance(), &[window]);
+    assert_eq!(record.effective_status(START - 1), ErStatus::Draining);
+    assert_eq!(record.effective_status(START), ErStatus::Offline);
+    assert_eq!(record.effective_status(END), ErStatus::Active);
+
+    // window which ends before it starts is rejected
+    record.set_maintenance(vec![MaintenanceWindow {
+        start: END,
+        end: START,
+        reason: MaintenanceReason::Other,
+    }]);
+    record.set_sequence(2);
+    let result = common::sync(&mut banks, &identity, record).await;
+    let err = result
+        .expect_err("invalid window should be rejected")
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MdpError::InvalidMaintenance as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_sync_fees() {
+    let TestEnv {
+        mut banks,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let pda = record.pda().0;
 
-    let result = common::unregister(&mut banks, &identity, pda).await;
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
 
-    assert_ok!(result, "error processing unregister transaction {}");
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let mut record = assert_ok!(result, "error querying registration PDA from banks {}");
+
+    let mut fees = record.fees().clone();
+    // decrease applies immediately
+    fees.micro_lamports_per_cu = 50;
+    record.set_fees(fees.clone());
+    record.set_sequence(1);
+    let result = common::sync(&mut banks, &identity, record).await;
+    assert_ok!(result, "error processing sync info transaction {}");
 
     let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let mut record = assert_ok!(result, "error querying registration PDA from banks {}");
+    assert_eq!(*record.fees(), fees);
+    let mut profile = TxProfile {
+        signatures: 1,
+        compute_units: 100_000,
+        features: record.features().clone(),
+        priority: true,
+    };
+    // (1000 + 5 + 5000) * 2
+    assert_eq!(record.estimate_fee(&profile, 0), Some(12_010));
+    // node cannot execute transactions using unsupported features
+    profile.features = profile.features.activate(Feature::HighResClock);
+    assert_eq!(record.estimate_fee(&profile, 0), None);
 
-    let acc = assert_ok!(result, "error querying unregistered PDA from banks {}");
-    assert!(
-        acc.is_none(),
-        "registration PDA hasn't been removed from banks"
+    // duplicate surcharge for the same feature is rejected
+    fees.surcharges.push(FeatureSurcharge {
+        feature: Feature::Randomness,
+        lamports: 1,
+    });
+    record.set_fees(fees);
+    record.set_sequence(2);
+    let result = common::sync(&mut banks, &identity, record).await;
+    let err = result
+        .expect_err("invalid fee schedule should be rejected")
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MdpError::InvalidFees as u32)
+        )
     );
 }
+
+#[tokio::test]
+async fn test_sync_legacy_record() {
+    let TestEnv {
+        mut context,
+        mut banks,
+        identity,
+        ..
+    } = common::setup().await;
+    let pda = common::record_pda(&identity.pubkey());
+
+    // record stored before the record layout was versioned
+    let legacy = RecordV0 {
+        identity: identity.pubkey(),
+        status: ErStatus::Active,
+        block_time_ms: 50,
+        base_fee: 1000,
+        features: FeaturesSet::default(),
+        load_average: 100,
+        country_code: CountryCode::from("083"),
+        addr: "https://241.132.2.41:9324/".to_string(),
+    };
+    let data = borsh::to_vec(&(0u8, &legacy)).unwrap();
+    let rent = banks.get_rent().await.unwrap();
+    let account = Account {
+        lamports: rent.minimum_balance(data.len()),
+        data,
+        owner: mdp::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    context.set_account(&pda, &AccountSharedData::from(account));
+
+    let mut record = common::load_record(&mut banks, pda).await.unwrap();
+    assert_eq!(record.fees().lamports_per_signature, 1000);
+    record.set_sequence(1);
+    record.set_status(ErStatus::Draining);
+    let result = common::sync(&mut banks, &identity, record).await;
+    assert_ok!(result, "error processing sync info transaction {}");
+
+    // the record is written back in the current layout
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}").unwrap();
+    assert_eq!(acc.data[0], V1_TAG);
+    let result = ErRecord::try_from_slice(&acc.data);
+    let record = assert_ok!(result, "error deserializing record {}");
+    assert_eq!(record.status(), ErStatus::Draining);
+    assert_eq!(record.sequence(), 1);
+    assert_eq!(record.addr(), legacy.addr);
+    assert_eq!(record.authority(), &identity.pubkey());
+    assert_eq!(record.fees().lamports_per_signature, 1000);
+}
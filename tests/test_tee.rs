@@ -0,0 +1,176 @@
+use borsh::BorshDeserialize;
+use common::TestEnv;
+use mdp::{
+    error::MdpError,
+    state::{
+        features::Feature,
+        record::ErRecord,
+        tee::{
+            measurement_hash, quote_hash, QuoteChunk, QuotePublication, QuoteType, TeeAttestation,
+        },
+    },
+};
+use program_test::BanksClient;
+use sdk::{
+    clock::Clock, hash::Hash, instruction::InstructionError, signature::Keypair, signer::Signer,
+    transaction::TransactionError,
+};
+
+pub mod common;
+
+/// Builds structurally valid SGX DCAP quote (version 3) with the given MRENCLAVE
+fn sgx_quote(mrenclave: u8) -> Vec<u8> {
+    let mut quote = vec![0u8; 1_200];
+    quote[..2].copy_from_slice(&3u16.to_le_bytes());
+    quote[112..144].fill(mrenclave);
+    quote
+}
+
+/// Uploads the quote in chunks, as it doesn't fit into single transaction
+async fn upload(banks: &mut BanksClient, identity: &Keypair, quote: &[u8]) {
+    for (i, bytes) in quote.chunks(600).enumerate() {
+        let chunk = QuoteChunk {
+            len: quote.len() as u32,
+            offset: (i * 600) as u32,
+            bytes: bytes.to_vec(),
+        };
+        let result = common::write_tee_quote(banks, identity, chunk).await;
+        assert_ok!(result, "error processing write quote transaction {}");
+    }
+}
+
+#[tokio::test]
+async fn test_tee_quote() {
+    let TestEnv {
+        mut banks,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let quote = sgx_quote(0xAB);
+    upload(&mut banks, &identity, &quote).await;
+
+    let result = banks.get_sysvar::<Clock>().await;
+    let now = assert_ok!(result, "error querying clock sysvar {}").unix_timestamp;
+    let publication = QuotePublication {
+        quote_type: QuoteType::SgxDcap,
+        expires_at: now + 86_400,
+    };
+    let result = common::publish_tee_quote(&mut banks, &identity, publication).await;
+    assert_ok!(result, "error processing publish quote transaction {}");
+
+    let result = banks
+        .get_account(common::record_pda(&identity.pubkey()))
+        .await;
+    let acc = assert_ok!(result, "error querying record PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let record = assert_ok!(result, "error deserializing ER record {}");
+    let tee = record.tee().expect("attestation should be published");
+    assert_eq!(tee.quote_hash, quote_hash(&quote));
+    assert_eq!(
+        Some(tee.measurement),
+        measurement_hash(QuoteType::SgxDcap, &quote)
+    );
+    assert!(record.features().contains(Feature::TeeAttestation));
+    assert!(record.tee_attested(now));
+    assert!(!record.tee_attested(now + 86_400));
+}
+
+#[tokio::test]
+async fn test_tee_quote_rejected() {
+    let TestEnv {
+        mut banks,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let expected = TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(MdpError::InvalidTeeQuote as u32),
+    );
+
+    // chunk past the end of the quote
+    let chunk = QuoteChunk {
+        len: 100,
+        offset: 50,
+        bytes: vec![1; 60],
+    };
+    let result = common::write_tee_quote(&mut banks, &identity, chunk).await;
+    let err = result.expect_err("out of bounds chunk should be rejected");
+    assert_eq!(err.unwrap(), expected);
+
+    upload(&mut banks, &identity, &sgx_quote(1)).await;
+
+    // SGX quote cannot be published as TDX one
+    let publication = QuotePublication {
+        quote_type: QuoteType::TdxDcap,
+        expires_at: i64::MAX,
+    };
+    let result = common::publish_tee_quote(&mut banks, &identity, publication).await;
+    let err = result.expect_err("mistyped quote should be rejected");
+    assert_eq!(err.unwrap(), expected);
+
+    // already expired attestation
+    let publication = QuotePublication {
+        quote_type: QuoteType::SgxDcap,
+        expires_at: 0,
+    };
+    let result = common::publish_tee_quote(&mut banks, &identity, publication).await;
+    let err = result.expect_err("expired attestation should be rejected");
+    assert_eq!(err.unwrap(), expected);
+}
+
+#[tokio::test]
+async fn test_tee_attestation_forged() {
+    let TestEnv {
+        mut banks,
+        identity,
+        context: _context,
+        ..
+    } = common::setup().await;
+
+    let expected = TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(MdpError::InvalidTeeQuote as u32),
+    );
+
+    // attestation cannot be registered without publishing the quote
+    let record = || common::test_record(identity.pubkey(), identity.pubkey());
+    let mut forged = record();
+    forged.set_tee(Some(TeeAttestation {
+        quote_type: QuoteType::SgxDcap,
+        measurement: Hash::new_from_array([1; 32]),
+        quote_hash: Hash::new_from_array([2; 32]),
+        expires_at: i64::MAX,
+    }));
+    let result = common::register(&mut banks, forged, &identity).await;
+    let err = result.expect_err("registration with attestation should be rejected");
+    assert_eq!(err.unwrap(), expected);
+
+    // neither can the feature be advertised
+    let mut forged = record();
+    forged.set_features(forged.features().clone().activate(Feature::TeeAttestation));
+    common::refresh_blockhash(&mut banks).await;
+    let result = common::register(&mut banks, forged, &identity).await;
+    let err = result.expect_err("registration with TEE feature should be rejected");
+    assert_eq!(err.unwrap(), expected);
+
+    let result = common::register(&mut banks, record(), &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    // nor enabled with sync
+    let mut forged = record();
+    forged.set_sequence(1);
+    forged.set_features(forged.features().clone().activate(Feature::TeeAttestation));
+    let result = common::sync(&mut banks, &identity, forged).await;
+    let err = result.expect_err("sync enabling TEE feature should be rejected");
+    assert_eq!(err.unwrap(), expected);
+}
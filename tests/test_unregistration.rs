@@ -1,10 +1,31 @@
-use common::TestEnv;
+use borsh::BorshDeserialize;
+use common::{TestEnv, UNBONDING_COOLDOWN_SECS};
+use mdp::{
+    error::MdpError,
+    instructions::Instruction,
+    state::{
+        dispute::{DisputeClaim, DisputeReason, Ruling, SlashRecipient},
+        record::ErRecord,
+        status::ErStatus,
+    },
+};
+use sdk::{
+    clock::Clock,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction as SolanaInstruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signer::Signer,
+    system_program,
+    transaction::Transaction,
+};
 
 pub mod common;
 
 #[tokio::test]
 async fn test_unregistration() {
     let TestEnv {
+        mut context,
         mut banks,
         identity,
         record,
@@ -15,15 +36,187 @@ async fn test_unregistration() {
     let result = common::register(&mut banks, record, &identity).await;
     assert_ok!(result, "error processing register transaction {}");
 
-    let result = common::unregister(&mut banks, &identity, pda).await;
+    // exit cannot be completed before it's started
+    let result = common::complete_unregister(&mut banks, &identity, pda).await;
+    let err = result.expect_err("exit should be started first").unwrap();
+    assert_eq!(err, common::custom(MdpError::ExitNotReady));
 
-    assert_ok!(result, "error processing unregister transaction {}");
+    let result = common::begin_unregister(&mut banks, &identity, pda).await;
+    assert_ok!(result, "error processing begin unregister transaction {}");
 
     let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    let result = ErRecord::try_from_slice(&acc.unwrap().data);
+    let mut record = assert_ok!(result, "error deserializing record {}");
+    let result = banks.get_sysvar::<Clock>().await;
+    let now = assert_ok!(result, "error querying clock sysvar {}").unix_timestamp;
+    assert_eq!(record.status(), ErStatus::Deregistering);
+    assert_eq!(record.exit_after(), Some(now + UNBONDING_COOLDOWN_SECS));
+    // node is hidden from discovery
+    assert_eq!(mdp::discovery::Filter::new(now).apply([&record]).count(), 0);
 
+    // node cannot sneak back into discovery during the cooldown
+    record.set_status(ErStatus::Active);
+    record.set_sequence(1);
+    let result = common::sync(&mut banks, &identity, record).await;
+    let err = result
+        .expect_err("status should stay deregistering")
+        .unwrap();
+    assert_eq!(err, common::custom(MdpError::Deregistering));
+
+    let result = common::complete_unregister(&mut banks, &identity, pda).await;
+    let err = result.expect_err("cooldown should pass first").unwrap();
+    assert_eq!(err, common::custom(MdpError::ExitNotReady));
+
+    common::advance_clock(&mut context, UNBONDING_COOLDOWN_SECS).await;
+    let result = common::complete_unregister(&mut banks, &identity, pda).await;
+    assert_ok!(
+        result,
+        "error processing complete unregister transaction {}"
+    );
+
+    let result = banks.get_account(pda).await;
     let acc = assert_ok!(result, "error querying unregistered PDA from banks {}");
     assert!(
         acc.is_none(),
         "registration PDA hasn't been removed from banks"
     );
 }
+
+#[tokio::test]
+async fn test_unregistration_to_recipient() {
+    let TestEnv {
+        mut context,
+        mut banks,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let pda = record.pda().0;
+    let treasury = Pubkey::new_unique();
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    let result = common::begin_unregister(&mut banks, &identity, pda).await;
+    assert_ok!(result, "error processing begin unregister transaction {}");
+
+    let result = banks.get_balance(pda).await;
+    let rent = assert_ok!(result, "error querying registration PDA balance {}");
+
+    common::advance_clock(&mut context, UNBONDING_COOLDOWN_SECS).await;
+    let result = common::complete_unregister_to(&mut banks, &identity, pda, Some(treasury)).await;
+    assert_ok!(
+        result,
+        "error processing complete unregister transaction {}"
+    );
+
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying unregistered PDA from banks {}");
+    assert!(
+        acc.is_none(),
+        "registration PDA hasn't been removed from banks"
+    );
+
+    let result = banks.get_balance(treasury).await;
+    let balance = assert_ok!(result, "error querying recipient balance {}");
+    assert_eq!(balance, rent, "recipient should receive the record's rent");
+}
+
+#[tokio::test]
+async fn test_unregistration_with_open_dispute() {
+    let TestEnv {
+        mut context,
+        mut banks,
+        payer,
+        admin,
+        identity,
+        record,
+    } = common::setup().await;
+    let pda = record.pda().0;
+    let recipient = Pubkey::new_unique();
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+    let result = common::bond(&mut banks, &identity, LAMPORTS_PER_SOL / 2).await;
+    assert_ok!(result, "error processing bond transaction {}");
+
+    let result = common::begin_unregister(&mut banks, &identity, pda).await;
+    assert_ok!(result, "error processing begin unregister transaction {}");
+
+    // misbehaviour can still be disputed during the cooldown
+    let claim = DisputeClaim {
+        identity: identity.pubkey(),
+        reason: DisputeReason::FalseStatus,
+        evidence: Hash::default(),
+        stake: LAMPORTS_PER_SOL / 10,
+    };
+    let result = common::open_dispute(&mut banks, &payer, claim).await;
+    assert_ok!(result, "error processing open dispute transaction {}");
+
+    common::advance_clock(&mut context, UNBONDING_COOLDOWN_SECS).await;
+    let result = common::complete_unregister(&mut banks, &identity, pda).await;
+    let err = result
+        .expect_err("open dispute should block the exit")
+        .unwrap();
+    assert_eq!(err, common::custom(MdpError::DisputesPending));
+
+    let ruling = Ruling {
+        upheld: true,
+        slash_to: SlashRecipient::Winner,
+    };
+    let result = common::resolve_dispute(
+        &mut banks,
+        &admin,
+        identity.pubkey(),
+        payer.pubkey(),
+        ruling,
+    )
+    .await;
+    assert_ok!(result, "error processing resolve dispute transaction {}");
+
+    let result = common::complete_unregister_to(&mut banks, &identity, pda, Some(recipient)).await;
+    assert_ok!(
+        result,
+        "error processing complete unregister transaction {}"
+    );
+
+    // what's left of the bond is released along with the record's rent
+    let result = banks.get_balance(recipient).await;
+    let balance = assert_ok!(result, "error querying recipient balance {}");
+    assert!(balance > LAMPORTS_PER_SOL / 2 - LAMPORTS_PER_SOL / 10);
+}
+
+#[tokio::test]
+async fn test_unregister_removed() {
+    let TestEnv {
+        mut banks,
+        identity,
+        record,
+        ..
+    } = common::setup().await;
+    let pda = record.pda().0;
+
+    let result = common::register(&mut banks, record, &identity).await;
+    assert_ok!(result, "error processing register transaction {}");
+
+    // immediate unregistration keeps its discriminant, but is rejected
+    let ix = Instruction::Unregister(identity.pubkey());
+    assert_eq!(borsh::to_vec(&ix).unwrap()[0], 1);
+    let accounts = vec![
+        AccountMeta::new(identity.pubkey(), true),
+        AccountMeta::new(pda, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    let ix = SolanaInstruction::new_with_borsh(mdp::ID, &ix, accounts);
+    let hash = banks.get_latest_blockhash().await.unwrap();
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&identity.pubkey()), &[&identity], hash);
+    let result = common::process(&mut banks, tx).await;
+    let err = result.expect_err("unregister should be rejected").unwrap();
+    assert_eq!(err, common::custom(MdpError::RemovedInstruction));
+
+    let result = banks.get_account(pda).await;
+    let acc = assert_ok!(result, "error querying registration PDA from banks {}");
+    assert!(acc.is_some(), "record should stay registered");
+}